@@ -0,0 +1,9 @@
+function init_game()
+	return {
+		-- Milliseconds before the next piece spawns, longer after clearing lines
+		delays = {
+			are = 100,
+			line_clear = 250,
+		},
+	}
+end
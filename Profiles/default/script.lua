@@ -1 +1 @@
-require "Profiles.defaults"
+require "defaults"
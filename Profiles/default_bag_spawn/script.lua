@@ -1,16 +1,12 @@
-require "Profiles.defaults"
-require "Profiles.bag_spawn"
-
-function spawn_piece(state)
-	return bag_spawn()
-end
+require "defaults"
 
 function init_game()
 	return {
 		width = 10,
 		height = 20,
 
-		start_piece = bag_spawn(),
+		pieces = pieces,
+		randomizer = "14-bag",
 
 		piece_view = {
 			size = 5,
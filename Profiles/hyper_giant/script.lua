@@ -1,10 +1,3 @@
-require "Profiles.defaults"
-require "Profiles.bag_spawn"
-
-function spawn_piece(state)
-	return bag_spawn()
-end
-
 function init_game()
 	return {
 		width = 50,
@@ -12,13 +5,13 @@ function init_game()
 
 		piece_tick = 100,
 
-		start_piece = bag_spawn(),
-
-		piece_view = {
-			size = 5,
-		},
-		piece_hold = {
-			enabled = true,
+		colors = {
+			field = {
+				from = { r = 0, g = 0, b = 0, a = 0xFF },
+				to = { r = 24, g = 24, b = 48, a = 0xFF },
+				direction = "rows",
+			},
+			grid = { r = 32, g = 32, b = 32, a = 0xFF },
 		},
 	}
 end
@@ -0,0 +1,22 @@
+pieces = _pieceSet("tetrominoes")
+
+function init_game()
+	return {
+		width = 10,
+		height = 20,
+
+		pieces = pieces,
+		randomizer = "random",
+
+		piece_view = {
+			size = 5,
+		},
+		piece_hold = {
+			enabled = true,
+		},
+	}
+end
+
+function on_place(state)
+	_solveField(state)
+end
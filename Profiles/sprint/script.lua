@@ -1,10 +1,5 @@
-require "Profiles.default_bag_spawn.script"
-
-function spawn_piece(state)
-	return bag_spawn()
-end
-
-local score = 40
+local goal = 40
+local score = goal
 
 function on_place(state)
 	local placed = _solveField(state)
@@ -12,28 +7,55 @@ function on_place(state)
 	if #placed > 0 then
 		score = score - #placed
 
+		if #placed >= 4 then
+			_event(state, "tetris")
+		end
+
 		if score <= 0 then
+			local time = _getStats(state).time
+			if time < storage.get("best_time", math.huge) then
+				storage.set("best_time", time)
+			end
+
 			_finishGame(state)
-		else
-			print(string.format("Remaining: %i", score))
 		end
 	end
 end
 
+function on_draw(state)
+	_drawText(state, {
+		relative = "panel",
+		x = 0.5,
+		y = 1.5,
+		size = 0.6,
+		text = string.format("Left: %i", math.max(score, 0)),
+	})
+
+	_drawProgress(state, {
+		relative = "panel",
+		x = 0.5,
+		y = 2.5,
+		w = 5,
+		h = 0.4,
+		value = (goal - score) / goal,
+		color = { r = 68, g = 210, b = 242, a = 0xFF },
+		background = { r = 0, g = 0, b = 0, a = 0xFF },
+	})
+
+	local best = storage.get("best_time")
+	if best then
+		_drawText(state, {
+			relative = "panel",
+			x = 0.5,
+			y = 3.5,
+			size = 0.6,
+			text = string.format("Best: %.2fs", best),
+		})
+	end
+end
+
 function init_game()
 	return {
-		width = 10,
-		height = 20,
-
-		start_piece = bag_spawn(),
-
 		piece_tick = 1000,
-
-		piece_view = {
-			size = 5,
-		},
-		piece_hold = {
-			enabled = true,
-		},
 	}
 end
@@ -1,28 +1,49 @@
+use std::any::Any;
+
 use log::error;
 
-pub struct Error;
+pub struct Error
+{
+	msg: String,
+}
+
+impl Error
+{
+	/// Human readable description of what went wrong. Lua errors include the source location and
+	/// a traceback.
+	pub fn message(&self) -> &str
+	{
+		&self.msg
+	}
+}
 
 impl std::fmt::Debug for Error
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
 	{
-		write!(f, "An unrecoverable error has occured.")
+		write!(f, "An unrecoverable error has occured.\n{}", self.msg)
 	}
 }
 
-impl<T: std::fmt::Display> From<T> for Error
+impl<T: std::fmt::Display + 'static> From<T> for Error
 {
 	fn from(e: T) -> Self
 	{
-		error!("{}", e);
-		Self {}
+		let msg = match (&e as &dyn Any).downcast_ref::<rlua::Error>()
+		{
+			Some(e) => crate::lua::describe_error(e),
+			None => e.to_string(),
+		};
+
+		error!("{}", msg);
+		Self { msg }
 	}
 }
 
 #[macro_export]
 macro_rules! err {
     ($x:expr, $msg:expr, $($param:expr),+) => {
-        $x.map_err(|_| { error!($msg, $($param,)+); return Error {} })
+        $x.map_err(|_| Error::from(format!($msg, $($param,)+)))
     };
 }
 
@@ -1,9 +1,299 @@
-use std::{fs::File, io::prelude::*, path::Path};
+use std::{
+	fs::File,
+	io::prelude::*,
+	path::{Path, PathBuf},
+};
 
 use log::*;
+use rlua::{prelude::*, StdLib};
 
 use crate::{err, error::Error};
 
+// -----------------------------------------------------------------------------
+// Module Resolution
+// -----------------------------------------------------------------------------
+
+/// Registry table mapping the modules loaded through the profile searcher to their files.
+const MODULES_KEY: &str = "dedtris.modules";
+
+/// Directories `require` searches for modules.
+#[derive(Clone)]
+pub struct SearchPaths
+{
+	/// Directory of the running profile.
+	pub profile:  PathBuf,
+	/// Directories of the profiles the running profile extends, nearest parent first.
+	pub parents:  Vec<PathBuf>,
+	/// Directory of modules shared between profiles.
+	pub library:  PathBuf,
+	/// Directory containing all profiles, making them requirable by name.
+	pub profiles: PathBuf,
+}
+
+impl SearchPaths
+{
+	/// Returns the files a module name can resolve to, in search order. A module `a.b` is searched
+	/// as `a/b.lua` in the profile, parent profile and library directories, followed by `b.lua` of
+	/// the profile `a`.
+	/// A lone profile name resolves to the profile's `script.lua`. Returns `None` if the name
+	/// contains components that could escape the search directories.
+	fn candidates(&self, name: &str) -> Option<Vec<PathBuf>>
+	{
+		let mut parts: Vec<&str> = name.split('.').collect();
+
+		if parts.iter().any(|p| {
+			p.is_empty() || !p.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+		})
+		{
+			return None;
+		}
+
+		// Names used to be written relative to the working directory, like
+		// `Profiles.default.script`.
+		let root_name = self.profiles.file_name().and_then(|n| n.to_str());
+		if parts.len() > 1 && root_name == Some(parts[0])
+		{
+			parts.remove(0);
+		}
+
+		let module_file = |dir: &Path, parts: &[&str]| {
+			let (last, dirs) = parts.split_last()?;
+			let mut path = dir.to_path_buf();
+			path.extend(dirs);
+			path.push(format!("{}.lua", last));
+			Some(path)
+		};
+
+		let by_name = if parts.len() == 1
+		{
+			self.profiles.join(parts[0]).join("script.lua")
+		}
+		else
+		{
+			module_file(&self.profiles, &parts)?
+		};
+
+		let mut v = vec![module_file(&self.profile, &parts)?];
+
+		for p in &self.parents
+		{
+			v.push(module_file(p, &parts)?);
+		}
+
+		v.push(module_file(&self.library, &parts)?);
+		v.push(by_name);
+
+		Some(v)
+	}
+}
+
+fn create_searcher<'a>(ctx: rlua::Context<'a>, paths: SearchPaths) -> LuaResult<LuaFunction<'a>>
+{
+	ctx.create_function(move |ctx, name: String| {
+		let candidates = match paths.candidates(&name)
+		{
+			Some(c) => c,
+			None =>
+			{
+				let msg = format!("module name '{}' is invalid", name);
+				return (msg, LuaNil).to_lua_multi(ctx);
+			},
+		};
+
+		match candidates.iter().find(|p| p.is_file())
+		{
+			Some(path) =>
+			{
+				let chunk = load_file(ctx, path)?;
+				let file = path.display().to_string();
+
+				let modules: LuaTable = ctx.named_registry_value(MODULES_KEY)?;
+				modules.set(name, file.clone())?;
+
+				(chunk, file).to_lua_multi(ctx)
+			},
+			None =>
+			{
+				let msg = candidates
+					.iter()
+					.map(|p| format!("no file '{}'", p.display()))
+					.collect::<Vec<_>>()
+					.join("\n\t");
+				(msg, LuaNil).to_lua_multi(ctx)
+			},
+		}
+	})
+}
+
+// -----------------------------------------------------------------------------
+// Sandbox
+// -----------------------------------------------------------------------------
+
+/// Functions of the `os` library that are kept inside the sandbox.
+const OS_WHITELIST: [&str; 4] = ["clock", "date", "difftime", "time"];
+
+/// Creates a Lua state for running profile scripts.
+///
+/// Untrusted states only get the side effect free parts of the standard library and `require` is
+/// restricted to the search paths. Trusted states get the full standard library and fall back to
+/// the default Lua searchers.
+pub fn create_lua(paths: &SearchPaths, trusted: bool) -> Result<rlua::Lua, Error>
+{
+	let lua = if trusted
+	{
+		warn!("Running trusted profile, the script has full access to the system.");
+		rlua::Lua::new()
+	}
+	else
+	{
+		rlua::Lua::new_with(
+			StdLib::BASE
+				| StdLib::COROUTINE
+				| StdLib::TABLE
+				| StdLib::OS | StdLib::STRING
+				| StdLib::UTF8
+				| StdLib::MATH
+				| StdLib::PACKAGE,
+		)
+	};
+
+	lua.context(|ctx| {
+		if !trusted
+		{
+			sandbox(ctx)?;
+		}
+
+		install_searcher(ctx, paths.clone(), trusted)
+	})?;
+
+	Ok(lua)
+}
+
+fn sandbox(ctx: rlua::Context) -> LuaResult<()>
+{
+	let g = ctx.globals();
+
+	g.set("dofile", LuaNil)?;
+	g.set("loadfile", LuaNil)?;
+
+	let os = ctx.create_table()?;
+	let full_os: LuaTable = g.get("os")?;
+	for name in OS_WHITELIST.iter()
+	{
+		os.set(*name, full_os.get::<_, LuaFunction>(*name)?)?;
+	}
+	g.set("os", os)?;
+
+	let package: LuaTable = g.get("package")?;
+	package.set("path", "")?;
+	package.set("cpath", "")?;
+	package.set("loadlib", LuaNil)?;
+	package.set("searchpath", LuaNil)?;
+
+	Ok(())
+}
+
+/// Puts the profile searcher right after the preload searcher. Untrusted states get no other
+/// searchers.
+fn install_searcher(ctx: rlua::Context, paths: SearchPaths, keep_defaults: bool) -> LuaResult<()>
+{
+	let package: LuaTable = ctx.globals().get("package")?;
+	let defaults: Vec<LuaFunction> =
+		package.get::<_, LuaTable>("searchers")?.sequence_values().collect::<LuaResult<_>>()?;
+
+	let mut searchers = vec![defaults[0].clone(), create_searcher(ctx, paths)?];
+	if keep_defaults
+	{
+		searchers.extend(defaults.into_iter().skip(1));
+	}
+
+	package.set("searchers", ctx.create_sequence_from(searchers)?)?;
+	ctx.set_named_registry_value(MODULES_KEY, ctx.create_table()?)?;
+
+	Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Reloading
+// -----------------------------------------------------------------------------
+
+/// Files of all modules loaded through the profile searcher.
+pub fn loaded_files(ctx: &rlua::Context) -> Result<Vec<PathBuf>, Error>
+{
+	let modules: LuaTable = ctx.named_registry_value(MODULES_KEY)?;
+
+	let mut v = Vec::new();
+	for pair in modules.pairs::<String, String>()
+	{
+		v.push(PathBuf::from(pair?.1));
+	}
+
+	Ok(v)
+}
+
+/// Executes the scripts again after unloading every module they required, so the following
+/// `require` calls load the current version of their files.
+pub fn reload_files(ctx: &rlua::Context, paths: &[PathBuf]) -> Result<(), Error>
+{
+	let modules: LuaTable = ctx.named_registry_value(MODULES_KEY)?;
+	let loaded: LuaTable = ctx.globals().get::<_, LuaTable>("package")?.get("loaded")?;
+
+	for pair in modules.pairs::<String, LuaValue>()
+	{
+		loaded.set(pair?.0, LuaNil)?;
+	}
+
+	ctx.set_named_registry_value(MODULES_KEY, ctx.create_table()?)?;
+
+	for p in paths
+	{
+		exec_file(ctx, p)?;
+	}
+
+	Ok(())
+}
+
+fn load_file<'a>(ctx: rlua::Context<'a>, path: &Path) -> LuaResult<LuaFunction<'a>>
+{
+	let buffer = std::fs::read(path).map_err(|e| {
+		LuaError::RuntimeError(format!("Couldn't read \"{}\": {}", path.display(), e))
+	})?;
+
+	ctx.load(&buffer).set_name(&chunk_name(path))?.into_function()
+}
+
+// -----------------------------------------------------------------------------
+// Error Reporting
+// -----------------------------------------------------------------------------
+
+/// Chunk name making Lua report locations as `path:line:`.
+fn chunk_name(path: &Path) -> String
+{
+	format!("@{}", path.display())
+}
+
+/// Describes a Lua error including where it happened. Errors raised inside Rust callbacks are
+/// unwrapped to their cause followed by the Lua traceback of the call.
+pub fn describe_error(e: &LuaError) -> String
+{
+	match e
+	{
+		LuaError::SyntaxError { message, .. } => format!("Syntax error: {}", message),
+		LuaError::RuntimeError(msg) => msg.clone(),
+		LuaError::CallbackError { traceback, cause } =>
+		{
+			format!("{}\n{}", describe_error(cause), traceback)
+		},
+		LuaError::FromLuaConversionError { from, to, message } => format!(
+			"Expected a {} but got a {}{}",
+			to,
+			from,
+			message.as_ref().map(|m| format!(": {}", m)).unwrap_or_default()
+		),
+		e => e.to_string(),
+	}
+}
+
 // -----------------------------------------------------------------------------
 // Lua routines
 // -----------------------------------------------------------------------------
@@ -17,7 +307,7 @@ pub fn exec_file(ctx: &rlua::Context, path: &Path) -> Result<(), Error>
 
 	info!("Loaded file \"{}\" with size {} Bytes.", path.display(), s);
 
-	ctx.load(&buffer).exec()?;
+	ctx.load(&buffer).set_name(&chunk_name(path))?.exec()?;
 
 	Ok(())
 }
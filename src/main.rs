@@ -7,16 +7,23 @@ mod error;
 mod lua;
 mod menu;
 mod runtime;
+mod settings;
+mod storage;
 
 fn main() -> Result<(), error::Error>
 {
 	// Init Logger
 	env_logger::init();
 
-	menu::print_banner();
+	if std::env::args().skip(1).any(|a| a == "--validate")
+	{
+		let valid = menu::validate::validate_profiles();
+		std::process::exit(if valid { 0 } else { 1 });
+	}
 
-	while !menu::start_menu()
-	{}
+	let mut sys = runtime::init_system()?;
+	let result = menu::start_menu(&mut sys);
 
-	Ok(())
+	runtime::remember_geometry(&mut sys);
+	result
 }
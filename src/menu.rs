@@ -1,83 +1,438 @@
-use std::io::Write;
+use sdl2::{
+	controller::Button,
+	event::Event,
+	keyboard::Keycode,
+	pixels::Color,
+	rect::Point,
+	render::{Texture, WindowCanvas},
+};
 
-mod profile;
+use self::profile::Profile;
+use crate::{
+	error::Error,
+	runtime::{self, font, Outcome, System},
+	settings::{self, GhostMode, ImageFormat, Settings, WindowMode},
+};
 
-pub struct MenuItem
+pub mod profile;
+pub mod validate;
+
+const BG_COLOR: Color = Color::RGB(24, 24, 32);
+const TEXT_COLOR: Color = Color::RGB(200, 200, 200);
+const SELECTED_COLOR: Color = Color::RGB(242, 210, 68);
+
+const TITLE_SCALE: u32 = 4;
+const TEXT_SCALE: u32 = 2;
+const MARGIN: i32 = 40;
+
+/// Entries of the main screen.
+const MAIN_ITEMS: [&str; 4] = ["Play", "Settings", "Validate profiles", "Quit"];
+
+/// Values the particle amount and the exported block size cycle through.
+const PARTICLE_AMOUNTS: [f64; 5] = [0.25, 0.5, 1.0, 1.5, 2.0];
+const EXPORT_BLOCK_SIZES: [u32; 5] = [16, 24, 32, 48, 64];
+
+// -----------------------------------------------------------------------------
+// Input
+// -----------------------------------------------------------------------------
+
+/// Menu input, from the keyboard or a controller.
+pub enum Input
 {
-	desc:   &'static str,
-	action: fn(),
+	Up,
+	Down,
+	Select,
+	Back,
 }
 
-impl std::fmt::Display for MenuItem
+pub fn input(event: &Event) -> Option<Input>
 {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	match event
 	{
-		write!(f, "{}", self.desc)
+		Event::KeyDown {
+			keycode: Some(k), ..
+		} => match k
+		{
+			Keycode::Up | Keycode::W => Some(Input::Up),
+			Keycode::Down | Keycode::S => Some(Input::Down),
+			Keycode::Return | Keycode::Space => Some(Input::Select),
+			Keycode::Escape | Keycode::Backspace => Some(Input::Back),
+			_ => None,
+		},
+
+		Event::ControllerButtonDown { button, .. } => match button
+		{
+			Button::DPadUp => Some(Input::Up),
+			Button::DPadDown => Some(Input::Down),
+			Button::A | Button::Start => Some(Input::Select),
+			Button::B | Button::Back => Some(Input::Back),
+			_ => None,
+		},
+
+		_ => None,
 	}
 }
 
-const MENU_ITEMS: [MenuItem; 1] = [MenuItem {
-	desc:   "Start a tetris game.",
-	action: profile::run_game,
-}];
+// -----------------------------------------------------------------------------
+// Menu Screens
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, PartialEq)]
+enum Screen
+{
+	Main,
+	Profiles,
+	/// The user settings, changed by selecting them and saved when leaving.
+	Settings,
+	/// Text shown until dismissed, like the validation report or a game error.
+	Report,
+}
+
+/// What the menu leaves to its caller.
+enum Choice
+{
+	Play(usize),
+	Quit,
+}
+
+struct Menu
+{
+	screen:   Screen,
+	selected: usize,
+
+	profiles: Vec<Profile>,
+
+	report: String,
+	scroll: usize,
+}
+
+/// The value following `v` in `all`, wrapping around. Values missing from `all` start it over.
+fn cycle<T: Copy + PartialEq>(all: &[T], v: T) -> T
+{
+	let i = all.iter().position(|a| *a == v).map_or(0, |i| i + 1);
+	all[i % all.len()]
+}
 
-pub fn start_menu() -> bool
+fn on_off(b: bool) -> &'static str
 {
-	if let Some(i) = request_item(&MENU_ITEMS)
+	if b
 	{
-		(MENU_ITEMS[i].action)();
-		false
+		"on"
 	}
 	else
 	{
-		true
+		"off"
 	}
 }
 
-pub fn request_item<T: std::fmt::Display>(items: &[T]) -> Option<usize>
+/// Entries of the settings screen showing the current values.
+fn settings_items(s: &Settings) -> Vec<String>
 {
-	let mut input = String::new();
+	vec![
+		format!("Particles: {}", on_off(s.particles)),
+		format!("Particle amount: {}%", (s.particle_amount * 100.0).round()),
+		format!("Window mode: {}", s.window.mode.name()),
+		format!("Vsync: {} (after a restart)", on_off(s.window.vsync)),
+		format!("Integer scaling: {}", on_off(s.window.integer_scaling)),
+		format!("Ghost piece: {}", s.ghost.name()),
+		format!("Screenshot format: {}", s.screenshot_format.extension()),
+		format!("Exported block size: {}px", s.export_block_size),
+	]
+}
 
-	loop
+/// Changes the setting of entry `i` of the settings screen to its next value.
+fn change_setting(s: &mut Settings, i: usize)
+{
+	let w = &mut s.window;
+
+	match i
+	{
+		0 => s.particles = !s.particles,
+		1 => s.particle_amount = cycle(&PARTICLE_AMOUNTS, s.particle_amount),
+		2 => w.mode = cycle(&WindowMode::ALL, w.mode),
+		3 => w.vsync = !w.vsync,
+		4 => w.integer_scaling = !w.integer_scaling,
+		5 => s.ghost = cycle(&GhostMode::ALL, s.ghost),
+		6 => s.screenshot_format = cycle(&ImageFormat::ALL, s.screenshot_format),
+		_ => s.export_block_size = cycle(&EXPORT_BLOCK_SIZES, s.export_block_size),
+	}
+}
+
+impl Menu
+{
+	fn items(&self, settings: &Settings) -> Vec<String>
 	{
-		for (i, item) in items.iter().enumerate()
+		match self.screen
 		{
-			println!("{}) {}", i + 1, item);
+			Screen::Main => MAIN_ITEMS.iter().map(|i| i.to_string()).collect(),
+			Screen::Profiles => self.profiles.iter().map(|p| p.name.clone()).collect(),
+			Screen::Settings => settings_items(settings),
+			Screen::Report => Vec::new(),
 		}
+	}
+
+	fn open(&mut self, screen: Screen)
+	{
+		self.screen = screen;
+		self.selected = 0;
+		self.scroll = 0;
+	}
+
+	fn show_report(&mut self, report: String)
+	{
+		self.report = report;
+		self.open(Screen::Report);
+	}
+
+	fn handle(&mut self, i: Input, settings: &mut Settings) -> Option<Choice>
+	{
+		let count = self.items(settings).len();
 
-		println!("{}) Exit.", items.len() + 1);
+		match (self.screen, i)
+		{
+			(Screen::Report, Input::Up) => self.scroll = self.scroll.saturating_sub(1),
+			(Screen::Report, Input::Down) => self.scroll += 1,
+			(Screen::Report, _) => self.open(Screen::Main),
+
+			(_, Input::Up) if count > 0 => self.selected = (self.selected + count - 1) % count,
+			(_, Input::Down) if count > 0 => self.selected = (self.selected + 1) % count,
 
-		print!("> ");
+			(Screen::Main, Input::Select) => match self.selected
+			{
+				0 => match profile::load_profiles()
+				{
+					Ok(ps) =>
+					{
+						self.profiles = ps;
+						self.open(Screen::Profiles);
+					},
+					Err(e) => self.show_report(e.message().to_owned()),
+				},
+				1 => self.open(Screen::Settings),
+				2 => self.show_report(validate::report().0),
+				_ => return Some(Choice::Quit),
+			},
+			(Screen::Main, Input::Back) => return Some(Choice::Quit),
 
-		std::io::stdout().flush().unwrap();
-		std::io::stdin().read_line(&mut input).unwrap();
+			(Screen::Profiles, Input::Select) if count > 0 =>
+			{
+				return Some(Choice::Play(self.selected))
+			},
+			(Screen::Profiles, Input::Back) => self.open(Screen::Main),
 
-		println!("\n-----------------\n");
+			(Screen::Settings, Input::Select) => change_setting(settings, self.selected),
+			(Screen::Settings, Input::Back) => match settings::save(settings)
+			{
+				Ok(()) => self.open(Screen::Main),
+				Err(e) => self.show_report(format!(
+					"The settings couldn't be saved:\n\n{}",
+					e.message()
+				)),
+			},
 
-		if let Ok(val) = input.trim().parse::<usize>()
+			_ => (),
+		}
+
+		None
+	}
+
+	fn draw(&self, canvas: &mut WindowCanvas, atlas: &mut Texture, settings: &Settings)
+	{
+		let (w, h) = canvas.window().size();
+		let line = (font::LINE_ADVANCE * TEXT_SCALE) as i32;
+		let columns =
+			|width: i32| (width.max(0) as u32 / (font::GLYPH_ADVANCE * TEXT_SCALE)) as usize;
+
+		canvas.set_draw_color(BG_COLOR);
+		canvas.clear();
+
+		let title = match self.screen
 		{
-			match val
+			Screen::Main => "dedtris",
+			Screen::Profiles => "Profiles",
+			Screen::Settings => "Settings",
+			Screen::Report => "Report",
+		};
+		font::draw_text(
+			canvas,
+			atlas,
+			Point::new(MARGIN, MARGIN),
+			title,
+			TITLE_SCALE,
+			Color::WHITE,
+		);
+
+		let top = MARGIN + (font::LINE_ADVANCE * TITLE_SCALE) as i32 + line;
+		let rows = ((h as i32 - top - MARGIN - 2 * line) / line).max(1) as usize;
+
+		match self.screen
+		{
+			Screen::Report =>
 			{
-				v if v <= items.len() && v > 0 => break Some(v - 1),
-				v if v == items.len() + 1 => break None,
-				_ => println!("Unrecognized option."),
-			}
+				let lines = font::wrap(&self.report, columns(w as i32 - 2 * MARGIN));
+				let scroll = self.scroll.min(lines.len().saturating_sub(rows));
+				let text =
+					lines.iter().skip(scroll).take(rows).cloned().collect::<Vec<_>>().join("\n");
+
+				font::draw_text(
+					canvas,
+					atlas,
+					Point::new(MARGIN, top),
+					&text,
+					TEXT_SCALE,
+					TEXT_COLOR,
+				);
+			},
+
+			_ =>
+			{
+				// Keep the selected item visible in long lists
+				let items = self.items(settings);
+				let first = (self.selected + 1).saturating_sub(rows);
+
+				for (i, item) in items.iter().enumerate().skip(first).take(rows)
+				{
+					let (text, color) = if i == self.selected
+					{
+						(format!("> {}", item), SELECTED_COLOR)
+					}
+					else
+					{
+						(format!("  {}", item), TEXT_COLOR)
+					};
+
+					let y = top + (i - first) as i32 * line;
+					font::draw_text(
+						canvas,
+						atlas,
+						Point::new(MARGIN, y),
+						&text,
+						TEXT_SCALE,
+						color,
+					);
+				}
+
+				if let (Screen::Profiles, Some(p)) = (self.screen, self.profiles.get(self.selected))
+				{
+					let x = w as i32 / 2;
+					let text =
+						font::wrap(&p.to_string(), columns(w as i32 / 2 - MARGIN)).join("\n");
+
+					font::draw_text(
+						canvas,
+						atlas,
+						Point::new(x, top),
+						&text,
+						TEXT_SCALE,
+						TEXT_COLOR,
+					);
+				}
+			},
 		}
-		else
+
+		let help = match self.screen
 		{
-			println!("Input isn't a number or is invalid.");
+			Screen::Report => "Up/Down: scroll   Enter/Escape: back",
+			Screen::Settings => "Up/Down: move   Enter: change   Escape: save and back",
+			_ => "Up/Down: move   Enter: select   Escape: back",
+		};
+		let y = h as i32 - MARGIN - line;
+		font::draw_text(
+			canvas,
+			atlas,
+			Point::new(MARGIN, y),
+			help,
+			TEXT_SCALE,
+			TEXT_COLOR,
+		);
+	}
+}
+
+/// Shows the menu until an entry leading out of it is chosen.
+fn run_menu(sys: &mut System, menu: &mut Menu) -> Result<Choice, Error>
+{
+	let System {
+		canvas,
+		tex_maker,
+		event_pump,
+		controller,
+		controllers,
+		settings,
+		quit,
+		..
+	} = sys;
+
+	let mut atlas = font::create_atlas(tex_maker)?;
+
+	loop
+	{
+		for event in event_pump.poll_iter()
+		{
+			runtime::open_controller(controller, controllers, &event);
+
+			if let Event::Quit { .. } = event
+			{
+				*quit = true;
+				return Ok(Choice::Quit);
+			}
+
+			let mode = settings.window.mode;
+			let choice = input(&event).and_then(|i| menu.handle(i, settings));
+
+			if settings.window.mode != mode
+			{
+				runtime::apply_window_mode(canvas, &mut settings.window, mode);
+			}
+
+			if let Some(c) = choice
+			{
+				return Ok(c);
+			}
 		}
 
-		input.clear();
+		menu.draw(canvas, &mut atlas, settings);
+		canvas.present();
+
+		::std::thread::sleep(std::time::Duration::new(0, 1_000_000_000u32 / 30));
 	}
 }
 
-#[rustfmt::skip]
-pub fn print_banner()
+/// Runs the in-window menu, starting the chosen games in the same window.
+pub fn start_menu(sys: &mut System) -> Result<(), Error>
 {
-	println!(
-"
-dedtris
-"
-	);
+	let mut menu = Menu {
+		screen:   Screen::Main,
+		selected: 0,
+		profiles: Vec::new(),
+		report:   String::new(),
+		scroll:   0,
+	};
+
+	loop
+	{
+		match run_menu(sys, &mut menu)?
+		{
+			Choice::Quit => return Ok(()),
+			Choice::Play(i) => loop
+			{
+				match runtime::start_tetris_game(sys, &menu.profiles[i])
+				{
+					Ok(Outcome::Retry) => continue,
+					Ok(Outcome::Menu) => (),
+					Ok(Outcome::Quit) => return Ok(()),
+					Err(e) => menu.show_report(format!(
+						"The game was stopped by an error:\n\n{}",
+						e.message()
+					)),
+				}
+
+				if sys.quit
+				{
+					return Ok(());
+				}
+
+				break;
+			},
+		}
+	}
 }
@@ -1,47 +1,172 @@
 use std::{
-	path::{Path, PathBuf},
+	path::{Component, Path, PathBuf},
 	str::FromStr,
 };
 
 use ini::Ini;
+use log::warn;
 
-use crate::{error::Error, runtime};
+use crate::{err, error::Error, lua, runtime::Skin};
+
+/// Name of the directory containing all profiles.
+pub const PROFILES_DIR: &str = "Profiles";
+
+/// Name of the directory inside the profiles directory holding modules shared between profiles.
+pub const LIBRARY_DIR: &str = "lib";
+
+/// Environment variable overriding the location of the profiles directory.
+pub const PROFILES_ENV: &str = "DEDTRIS_PROFILES";
 
 pub struct Profile
 {
-	name: String,
-	desc: String,
-	lua:  PathBuf,
+	pub name:    String,
+	pub desc:    String,
+	pub author:  Option<String>,
+	pub version: Option<String>,
+	pub tags:    Vec<String>,
+	pub extends: Option<String>,
+	pub skin:    Skin,
+	pub dir:     PathBuf,
+	pub lua:     PathBuf,
+	pub trusted: bool,
+}
+
+impl Profile
+{
+	/// Identifier of the profile, the name of its directory.
+	pub fn id(&self) -> String
+	{
+		self.dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+	}
 }
 
 impl std::fmt::Display for Profile
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
 	{
-		write!(f, "{}: \n{}\n", self.name, self.desc)
+		write!(f, "{}", self.name)?;
+
+		if let Some(v) = &self.version
+		{
+			write!(f, " {}", v)?;
+		}
+
+		if let Some(a) = &self.author
+		{
+			write!(f, " by {}", a)?;
+		}
+
+		if !self.tags.is_empty()
+		{
+			write!(f, " [{}]", self.tags.join(", "))?;
+		}
+
+		write!(f, ": \n{}\n", self.desc)
 	}
 }
 
-pub fn run_game()
+/// Locates the profiles directory. Checked in order are the `DEDTRIS_PROFILES` environment
+/// variable, the working directory and the directory of the executable.
+pub fn profiles_dir() -> PathBuf
 {
-	let profiles = match load_profiles()
+	if let Some(p) = std::env::var_os(PROFILES_ENV)
 	{
-		Ok(ps) => ps,
-		_ => return,
-	};
+		return PathBuf::from(p);
+	}
 
-	let i = match super::request_item(&profiles)
+	let local = PathBuf::from(PROFILES_DIR);
+	if local.is_dir()
 	{
-		Some(i) => i,
-		_ => return,
-	};
+		return local;
+	}
 
-	runtime::start_tetris_game(&profiles[i].lua);
+	std::env::current_exe()
+		.ok()
+		.and_then(|exe| exe.parent().map(|d| d.join(PROFILES_DIR)))
+		.filter(|p| p.is_dir())
+		.unwrap_or(local)
+}
+
+/// Returns the profile followed by the profiles it extends, nearest parent first.
+pub fn profile_chain(profile: &Profile) -> Result<Vec<Profile>, Error>
+{
+	let mut chain = vec![read_profile(&profile.dir)?];
+
+	while let Some(parent) = chain.last().and_then(|p| p.extends.clone())
+	{
+		let mut components = Path::new(&parent).components();
+		if !matches!(
+			(components.next(), components.next()),
+			(Some(Component::Normal(_)), None)
+		)
+		{
+			return Err(Error::from(format!(
+				"Profile \"{}\" extends \"{}\", which isn't a profile directory name.",
+				chain.last().map(Profile::id).unwrap_or_default(),
+				parent
+			)));
+		}
+
+		let dir = profiles_dir().join(&parent);
+
+		if chain.iter().any(|p| p.dir == dir)
+		{
+			let names: Vec<String> = chain.iter().map(Profile::id).collect();
+
+			return Err(Error::from(format!(
+				"Profile \"{}\" extends itself: {} -> {}.",
+				profile.id(),
+				names.join(" -> "),
+				parent
+			)));
+		}
+
+		let p = read_profile(&dir).map_err(|e| {
+			Error::from(format!(
+				"Can't load \"{}\" extended by \"{}\": {}",
+				parent,
+				chain.last().map(Profile::id).unwrap_or_default(),
+				e.message()
+			))
+		})?;
+
+		chain.push(p);
+	}
+
+	Ok(chain)
+}
+
+/// Locates a file shipped with a profile, searching the profile directory and the directories of
+/// its parents. Returns `None` for names leaving the profile directories.
+pub fn find_resource(chain: &[Profile], name: &str) -> Option<PathBuf>
+{
+	let path = Path::new(name);
+
+	if !path.components().all(|c| matches!(c, Component::Normal(_)))
+	{
+		return None;
+	}
+
+	chain.iter().map(|p| p.dir.join(path)).find(|p| p.is_file())
+}
+
+/// Search paths of a profile given its chain of parents as returned by `profile_chain`.
+pub fn search_paths(chain: &[Profile]) -> lua::SearchPaths
+{
+	let profiles = profiles_dir();
+	let dirs: Vec<PathBuf> = chain.iter().map(|p| p.dir.clone()).collect();
+
+	lua::SearchPaths {
+		profile: dirs.first().cloned().unwrap_or_default(),
+		parents: dirs.iter().skip(1).cloned().collect(),
+		library: profiles.join(LIBRARY_DIR),
+		profiles,
+	}
 }
 
 pub fn load_profiles() -> Result<Vec<Profile>, Error>
 {
-	let p = Path::new("Profiles");
+	let p = profiles_dir();
 
 	let mut v = Vec::new();
 
@@ -49,7 +174,7 @@ pub fn load_profiles() -> Result<Vec<Profile>, Error>
 	{
 		let entry = entry?;
 		let path = entry.path();
-		if path.is_dir()
+		if is_profile_dir(&path)
 		{
 			if let Some(profile) = load_profile(&path)
 			{
@@ -61,21 +186,77 @@ pub fn load_profiles() -> Result<Vec<Profile>, Error>
 	Ok(v)
 }
 
+/// Whether a directory inside the profiles directory is meant to be a profile.
+pub fn is_profile_dir(p: &Path) -> bool
+{
+	p.is_dir() && p.file_name() != Some(LIBRARY_DIR.as_ref())
+}
+
 pub fn load_profile(p: &Path) -> Option<Profile>
 {
-	let ini = Ini::load_from_file(p.join("config.ini")).ok()?;
+	match read_profile(p)
+	{
+		Ok(profile) => Some(profile),
+		Err(e) =>
+		{
+			warn!("Skipping profile \"{}\": {}", p.display(), e.message());
+			None
+		},
+	}
+}
+
+/// Reads the `config.ini` of a profile directory and locates its script.
+pub fn read_profile(p: &Path) -> Result<Profile, Error>
+{
+	let config = p.join("config.ini");
+	let ini = err!(
+		Ini::load_from_file(&config),
+		"Can't read \"{}\".",
+		config.display()
+	)?;
 	let sec = ini.general_section();
 
-	let name = load_property(&sec, "name")?;
-	let desc = load_property(&sec, "description")?;
+	let required = |key| {
+		load_property::<String>(sec, key)
+			.ok_or_else(|| Error::from(format!("\"config.ini\" has no \"{}\".", key)))
+	};
+
+	let name = required("name")?;
+	let desc = required("description")?;
+	let author = load_property(sec, "author");
+	let version = load_property(sec, "version");
+	let trusted = load_property(sec, "trusted").unwrap_or(false);
+	let extends = load_property(sec, "extends");
+
+	let skin = Skin {
+		texture:      load_property(sec, "skin"),
+		ghost_tile:   load_property(sec, "ghost_tile"),
+		garbage_tile: load_property(sec, "garbage_tile"),
+	};
+
+	let tags = sec
+		.get("tags")
+		.map(|t| t.split(',').map(str::trim).filter(|t| !t.is_empty()).map(String::from).collect())
+		.unwrap_or_default();
 
 	let lua = p.join("script.lua");
 	if !lua.is_file()
 	{
-		return None;
+		return Err(Error::from("The profile has no \"script.lua\"."));
 	}
 
-	Some(Profile { name, desc, lua })
+	Ok(Profile {
+		name,
+		desc,
+		author,
+		version,
+		tags,
+		extends,
+		skin,
+		dir: p.to_path_buf(),
+		lua,
+		trusted,
+	})
 }
 
 pub fn load_property<T: FromStr>(sec: &ini::Properties, key: &str) -> Option<T>
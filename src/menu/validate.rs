@@ -0,0 +1,118 @@
+use std::{fmt::Write, path::Path};
+
+use super::profile::{self, Profile};
+use crate::runtime;
+
+/// Problems found in a single profile.
+struct Report
+{
+	errors:   Vec<String>,
+	warnings: Vec<String>,
+}
+
+fn check_metadata(p: &Profile, report: &mut Report)
+{
+	if p.author.is_none()
+	{
+		report.warnings.push("\"config.ini\" has no \"author\".".to_owned());
+	}
+
+	if p.version.is_none()
+	{
+		report.warnings.push("\"config.ini\" has no \"version\".".to_owned());
+	}
+}
+
+fn validate(dir: &Path) -> Report
+{
+	let mut report = Report {
+		errors:   Vec::new(),
+		warnings: Vec::new(),
+	};
+
+	match profile::read_profile(dir)
+	{
+		Ok(p) =>
+		{
+			check_metadata(&p, &mut report);
+
+			match runtime::validate_profile(&p)
+			{
+				Ok(w) => report.warnings.extend(w),
+				Err(e) => report.errors.push(e.message().to_owned()),
+			}
+		},
+		Err(e) => report.errors.push(e.message().to_owned()),
+	}
+
+	report
+}
+
+/// Validates every profile and describes the findings. Returns `false` if any profile has errors.
+pub fn report() -> (String, bool)
+{
+	let dir = profile::profiles_dir();
+
+	let mut dirs: Vec<_> = match std::fs::read_dir(&dir)
+	{
+		Ok(entries) => entries
+			.filter_map(|e| e.ok().map(|e| e.path()))
+			.filter(|p| profile::is_profile_dir(p))
+			.collect(),
+		Err(e) => return (format!("Can't read \"{}\": {}", dir.display(), e), false),
+	};
+	dirs.sort();
+
+	let mut out = String::new();
+	let mut failed = 0;
+
+	for d in &dirs
+	{
+		let report = validate(d);
+
+		let status = if report.errors.is_empty()
+		{
+			"ok"
+		}
+		else
+		{
+			"FAILED"
+		};
+		let _ = writeln!(
+			out,
+			"{}: {}",
+			d.file_name().unwrap_or_default().to_string_lossy(),
+			status
+		);
+
+		for e in &report.errors
+		{
+			let _ = writeln!(out, "    error: {}", e.replace('\n', "\n    "));
+		}
+
+		for w in &report.warnings
+		{
+			let _ = writeln!(out, "    warning: {}", w);
+		}
+
+		failed += !report.errors.is_empty() as usize;
+	}
+
+	let _ = write!(
+		out,
+		"\n{} of {} profiles are valid.",
+		dirs.len() - failed,
+		dirs.len()
+	);
+
+	(out, failed == 0)
+}
+
+/// Validates every profile and prints the report. Returns `false` if any profile has errors.
+pub fn validate_profiles() -> bool
+{
+	let (report, valid) = report();
+	println!("{}\n", report);
+
+	valid
+}
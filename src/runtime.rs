@@ -1,40 +1,94 @@
-use std::{path::Path, time::Duration};
+use std::{path::PathBuf, time::Duration};
 
-use log::info;
+use log::{info, warn};
 use sdl2::{
+	controller::GameController,
 	event::{Event, WindowEvent},
 	keyboard::Keycode,
 	pixels::Color,
-	rect::{Point, Rect},
-	render::{TextureCreator, WindowCanvas},
-	video::WindowContext,
-	Sdl, VideoSubsystem,
+	rect::Point,
+	render::{Canvas, RenderTarget, TextureCreator, WindowCanvas},
+	video::{FullscreenType, WindowContext},
+	EventPump, GameControllerSubsystem, VideoSubsystem,
 };
 
 use self::{
 	game::Game,
-	state::{Direction, TetrisState},
+	state::{
+		flags::{self, Flag},
+		Direction, Rotation, TetrisState,
+	},
+};
+use crate::{
+	error::Error,
+	lua,
+	menu::profile::{self, Profile},
+	settings::{self, Settings, WindowMode, WindowSettings},
+	storage,
 };
-use crate::{error::Error, lua};
 
 mod drawer;
 mod game;
 mod profile_api;
+mod results;
 mod state;
+#[cfg(test)]
+mod tests;
+mod watcher;
+
+pub use self::{drawer::font, results::Outcome};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Piece
 {
 	pub dim:    u32,
 	pub colors: Vec<Color>,
+	pub tiles:  Vec<u32>,
+	pub blocks: Vec<Point>,
+
+	/// Explicit shapes of every orientation, empty if the piece rotates about its bounding box.
+	pub rotations: Vec<Shape>,
+	pub rotation:  usize,
+}
+
+/// Blocks of a piece orientation with the color and texture tile of each block.
+#[derive(Debug, Clone)]
+pub struct Shape
+{
 	pub blocks: Vec<Point>,
+	pub colors: Vec<Color>,
+	pub tiles:  Vec<u32>,
+}
+
+/// Block skin of a profile, given in `config.ini` or the `skin` table of `init_game`.
+#[derive(Debug, Clone, Default)]
+pub struct Skin
+{
+	/// Tilesheet file, searched in the profile directory and the directories of its parents.
+	pub texture:      Option<String>,
+	/// Tile of the ghost piece, drawn instead of a translucent copy of the player piece.
+	pub ghost_tile:   Option<u32>,
+	/// Tile of blocks added to the field without one, like garbage.
+	pub garbage_tile: Option<u32>,
+}
+
+impl Skin
+{
+	/// Fills the settings missing in this skin from `other`.
+	pub fn or(self, other: &Skin) -> Skin
+	{
+		Skin {
+			texture:      self.texture.or_else(|| other.texture.clone()),
+			ghost_tile:   self.ghost_tile.or(other.ghost_tile),
+			garbage_tile: self.garbage_tile.or(other.garbage_tile),
+		}
+	}
 }
 
 type Size = (u32, u32);
 
-pub struct Framework<'a, 'b, 'd, 'e, 'f, 'g>
+pub struct Framework<'b, 'd, 'e, 'f, 'g>
 {
-	pub sdl:       &'a Sdl,
 	pub video:     &'b VideoSubsystem,
 	pub canvas:    &'d mut WindowCanvas,
 	pub tex_maker: &'e TextureCreator<WindowContext>,
@@ -45,104 +99,300 @@ pub struct Framework<'a, 'b, 'd, 'e, 'f, 'g>
 // Game Runtime
 // -----------------------------------------------------------------------------
 
-/// Starts a window and plays the tetris game
-///
-/// # Arguments
-///
-/// * `sdl_context` SDL context
-/// * `video_sys` Video subsystem
-pub fn start_tetris_game(profile: &Path) -> Result<(), Error>
+/// Scripts of a profile chain in execution order, parents first.
+fn chain_scripts(chain: &[Profile]) -> Vec<PathBuf>
+{
+	chain.iter().rev().map(|p| p.lua.clone()).collect()
+}
+
+/// Combines the skin of `init_game` with the ones of the profile chain's `config.ini` and locates
+/// its texture, falling back to the default blocks.
+fn resolve_skin(chain: &[Profile], skin: &Skin) -> Result<(Skin, PathBuf), Error>
+{
+	let skin = chain.iter().fold(skin.clone(), |s, p| s.or(&p.skin));
+
+	let texture = match &skin.texture
+	{
+		Some(name) => profile::find_resource(chain, name)
+			.ok_or_else(|| Error::from(format!("Skin texture \"{}\" not found.", name)))?,
+		None => profile::profiles_dir().join("default/template.bmp"),
+	};
+
+	Ok((skin, texture))
+}
+
+/// Runs the scripts of the profile and its parents and evaluates their `init_game`.
+fn load_script(
+	ctx: &rlua::Context, profile: &Profile, chain: &[Profile],
+) -> Result<profile_api::Profile, Error>
+{
+	profile_api::load_defaults(ctx, &profile::search_paths(chain))?;
+	storage::load(ctx, &storage::profile_file(&profile.id()))?;
+	profile_api::exec_scripts(ctx, &chain_scripts(chain))?;
+
+	profile_api::load(ctx)
+}
+
+/// Loads the profile in a fresh Lua context and checks it like starting a game would, without
+/// opening a window. Returns warnings about parts of the profile that are likely unintended.
+pub fn validate_profile(profile: &Profile) -> Result<Vec<String>, Error>
+{
+	let chain = profile::profile_chain(profile)?;
+	let lua = lua::create_lua(&profile::search_paths(&chain), profile.trusted)?;
+
+	lua.context(|ctx| {
+		let t = load_script(&ctx, profile, &chain)?;
+		resolve_skin(&chain, &t.skin)?;
+
+		profile_api::check(&ctx, &t)
+	})
+}
+
+/// SDL state shared by the menu and the games, so every game runs in the same window.
+pub struct System
+{
+	pub video:      VideoSubsystem,
+	pub canvas:     WindowCanvas,
+	pub tex_maker:  TextureCreator<WindowContext>,
+	pub event_pump: EventPump,
+
+	pub controller:  GameControllerSubsystem,
+	pub controllers: Vec<GameController>,
+
+	pub settings: Settings,
+
+	/// Set once the window was closed.
+	pub quit: bool,
+}
+
+/// Title of the window outside of games.
+pub const WINDOW_TITLE: &str = "dedtris";
+
+pub fn init_system() -> Result<System, Error>
 {
 	info!("Initializing SDL2 and its subsystems.");
 
-	let sdl_context = sdl2::init()?;
-	let video_sys = sdl_context.video()?;
+	let sdl = sdl2::init()?;
+	let video = sdl.video()?;
+	let controller = sdl.game_controller()?;
 
-	info!("Initializing Lua plugin enviroment.",);
+	info!("Constructing window.");
 
-	let lua = rlua::Lua::new();
+	let settings = settings::load();
+	let ws = &settings.window;
 
-	lua.context::<_, Result<(), Error>>(|ctx| {
-		let t = {
-			profile_api::load_defaults(&ctx)?;
-			lua::exec_file(&ctx, profile)?;
+	let mut window = video.window(WINDOW_TITLE, ws.size.0, ws.size.1);
 
-			profile_api::load(&ctx)?
-		};
+	match ws.position
+	{
+		Some((x, y)) => window.position(x, y),
+		None => window.position_centered(),
+	};
 
-		info!("Constructing window.");
+	match ws.mode
+	{
+		WindowMode::Windowed => window.resizable(),
+		WindowMode::Fullscreen => window.fullscreen(),
+		WindowMode::Borderless => window.fullscreen_desktop(),
+	};
 
-		const WINDOW_SIZE: (u32, u32) = (1080, 720);
+	let window = window.build()?;
 
-		let window = video_sys
-			.window("Tetris", WINDOW_SIZE.0, WINDOW_SIZE.1)
-			.position_centered()
-			.resizable()
-			.build()?;
+	info!("Initializing renderer.");
 
-		info!("Initializing renderer.");
+	let mut canvas = window.into_canvas().accelerated().target_texture();
+	if ws.vsync
+	{
+		canvas = canvas.present_vsync();
+	}
+	let mut canvas = canvas.build()?;
+
+	canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+	canvas.set_draw_color(Color::RGB(0, 255, 255));
+	canvas.clear();
+	canvas.present();
+
+	let tex_maker = canvas.texture_creator();
+	let event_pump = sdl.event_pump()?;
+
+	Ok(System {
+		video,
+		canvas,
+		tex_maker,
+		event_pump,
+		controller,
+		controllers: Vec::new(),
+		settings,
+		quit: false,
+	})
+}
 
-		let mut canvas = {
-			let mut canvas = window.into_canvas().accelerated().target_texture().build()?;
+/// Stores the size and position of the window in the settings, to restore them on the next run.
+/// Fullscreen windows keep the last windowed geometry.
+pub fn remember_geometry(sys: &mut System)
+{
+	let window = sys.canvas.window();
+	let ws = &mut sys.settings.window;
 
-			canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
-			canvas.set_draw_color(Color::RGB(0, 255, 255));
-			canvas.clear();
-			canvas.present();
+	if ws.mode != WindowMode::Windowed
+	{
+		return;
+	}
 
-			canvas
-		};
+	ws.size = window.size();
+	ws.position = Some(window.position());
+
+	if let Err(e) = settings::save(&sys.settings)
+	{
+		warn!("Couldn't save the window geometry: {}", e.message());
+	}
+}
+
+/// Switches the window to the mode of `ws`, keeping the windowed geometry when leaving the
+/// `previous` mode.
+pub fn apply_window_mode(canvas: &mut WindowCanvas, ws: &mut WindowSettings, previous: WindowMode)
+{
+	let window = canvas.window_mut();
+
+	if previous == WindowMode::Windowed
+	{
+		ws.size = window.size();
+		ws.position = Some(window.position());
+	}
+
+	let fullscreen = match ws.mode
+	{
+		WindowMode::Windowed => FullscreenType::Off,
+		WindowMode::Fullscreen => FullscreenType::True,
+		WindowMode::Borderless => FullscreenType::Desktop,
+	};
+
+	if let Err(e) = window.set_fullscreen(fullscreen)
+	{
+		warn!("Can't switch the window to {}: {}", ws.mode.name(), e);
+	}
+}
+
+/// Opens controllers as they are connected, they only report input while open.
+pub fn open_controller(
+	subsystem: &GameControllerSubsystem, controllers: &mut Vec<GameController>, event: &Event,
+)
+{
+	if let Event::ControllerDeviceAdded { which, .. } = event
+	{
+		match subsystem.open(*which)
+		{
+			Ok(c) =>
+			{
+				info!("Opened controller \"{}\".", c.name());
+				controllers.push(c);
+			},
+			Err(e) => warn!("Can't open controller {}: {}", which, e),
+		}
+	}
+}
 
-		let tex_maker = canvas.texture_creator();
+/// Plays a game of the profile, followed by its results unless the window was closed.
+///
+/// # Arguments
+///
+/// * `sys` SDL state whose window the game is played in
+/// * `profile` Profile whose script drives the game
+pub fn start_tetris_game(sys: &mut System, profile: &Profile) -> Result<Outcome, Error>
+{
+	info!("Initializing Lua plugin enviroment.",);
+
+	let chain = profile::profile_chain(profile)?;
+	let lua = lua::create_lua(&profile::search_paths(&chain), profile.trusted)?;
+
+	let System {
+		video,
+		canvas,
+		tex_maker,
+		event_pump,
+		settings,
+		quit,
+		..
+	} = sys;
+
+	lua.context::<_, Result<Outcome, Error>>(|ctx| {
+		let t = load_script(&ctx, profile, &chain)?;
+
+		canvas.window_mut().set_title(&profile.name)?;
+		let win_dim = canvas.window().size();
 
 		info!("Initializing tetris game.");
 
 		let mut game = {
 			let fw = Framework {
-				sdl:       &sdl_context,
-				video:     &video_sys,
-				canvas:    &mut canvas,
-				tex_maker: &tex_maker,
-				lua:       &ctx,
+				video,
+				canvas,
+				tex_maker,
+				lua: &ctx,
 			};
 
-			game::init_game(fw, WINDOW_SIZE, t)?
+			game::init_game(fw, win_dim, t, &chain, settings)?
 		};
 
 		// Event Loop
 
 		info!("Beginning Game.");
 
-		let sdl = game.fw.sdl;
-		let mut event_pump = sdl.event_pump().unwrap();
-
 		'running: loop
 		{
 			let canvas = &mut game.fw.canvas;
-			canvas.set_draw_color(Color::GRAY);
+			canvas.set_draw_color(game.rend.bg_color);
 			canvas.clear();
 
 			for event in event_pump.poll_iter()
 			{
 				match event
 				{
-					Event::Quit { .. }
-					| Event::KeyDown {
+					Event::Quit { .. } =>
+					{
+						*quit = true;
+						break 'running;
+					},
+					Event::KeyDown {
 						keycode: Some(Keycode::Escape),
 						..
 					} => break 'running,
-					_ =>
+					_ => match handle_event(&event, &mut game)
 					{
-						if !handle_event(&event, &mut game)?
-						{
-							break 'running;
-						}
+						Ok(true) => (),
+						Ok(false) => break 'running,
+						Err(e) => game.report(e),
 					},
 				}
 			}
 
-			update(&mut game)?;
-			draw(&mut game);
+			if let Err(e) = game.hot_reload()
+			{
+				game.report(e);
+			}
+
+			if let Err(e) = update(&mut game)
+			{
+				game.report(e);
+			}
+
+			if game.state.exit
+			{
+				break 'running;
+			}
+
+			if let Err(e) = draw(&mut game)
+			{
+				game.report(e);
+			}
+
+			if game.screenshot
+			{
+				if let Err(e) = game.save_frame()
+				{
+					game.report(e);
+				}
+			}
 
 			let canvas = &mut game.fw.canvas;
 			canvas.present();
@@ -150,10 +400,55 @@ pub fn start_tetris_game(profile: &Path) -> Result<(), Error>
 			::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 30));
 		}
 
-		let state = &game.state;
+		let state = &mut game.state;
+		// Games left with Escape or by closing the window end without a game over
+		let finished = state.exit;
+		state.finish();
 		state.output_score();
 
-		Ok(())
+		storage::flush(&ctx)?;
+
+		let outcome = if *quit
+		{
+			Outcome::Quit
+		}
+		else
+		{
+			let r = results::Results {
+				time: state.elapsed(),
+				record: storage::Record {
+					score: state.score,
+					lines: state.lines_cleared,
+					pps:   state.pps(),
+				},
+				best: storage::load_record(&profile.id()),
+				samples: std::mem::take(&mut state.samples),
+				finished,
+			};
+
+			// A few quick pieces don't make a meaningful speed
+			let mut record = r.record;
+			if state.pieces_placed < storage::MIN_PPS_PIECES
+			{
+				record.pps = 0.0;
+			}
+
+			if finished
+			{
+				if let Err(e) = storage::save_record(&profile.id(), &record)
+				{
+					warn!("Couldn't save the personal bests: {}", e.message());
+				}
+			}
+
+			let outcome = results::show(&mut game, event_pump, &r);
+			*quit = outcome == Outcome::Quit;
+			outcome
+		};
+
+		game.fw.canvas.window_mut().set_title(WINDOW_TITLE)?;
+
+		Ok(outcome)
 	})
 }
 
@@ -161,6 +456,37 @@ pub fn handle_event<'a>(event: &Event, game: &mut Game) -> Result<bool, Error>
 {
 	match event
 	{
+		Event::KeyDown {
+			keycode: Some(Keycode::F5),
+			..
+		} => game.reload()?,
+
+		Event::KeyDown {
+			keycode: Some(Keycode::F12),
+			..
+		} => game.screenshot = true,
+
+		Event::KeyDown {
+			keycode: Some(Keycode::F9),
+			..
+		} => game.export_field()?,
+
+		// While an error is shown, keys only dismiss it
+		Event::KeyDown {
+			keycode: Some(Keycode::Return),
+			..
+		} if game.error.is_some() => game.resume(),
+
+		Event::KeyDown { .. } if game.error.is_some() => (),
+
+		// Between placing a piece and spawning the next one there is no piece to control, actions
+		// included
+		Event::KeyDown { .. } if game.state.waiting() => (),
+
+		Event::KeyDown {
+			keycode: Some(x), ..
+		} if game.action(*x).is_some() => game.run_action(*x)?,
+
 		Event::KeyDown {
 			keycode: Some(x), ..
 		} => match x
@@ -182,10 +508,14 @@ pub fn handle_event<'a>(event: &Event, game: &mut Game) -> Result<bool, Error>
 				return Ok(game.move_piece_down()?);
 			},
 
-			Keycode::Up =>
+			Keycode::Up | Keycode::X =>
 			{
-				let state = &mut game.state;
-				state.rotate();
+				game.rotate(Rotation::Clockwise)?;
+			},
+
+			Keycode::Z =>
+			{
+				game.rotate(Rotation::CounterClockwise)?;
 			},
 
 			Keycode::LShift =>
@@ -219,106 +549,208 @@ pub fn handle_event<'a>(event: &Event, game: &mut Game) -> Result<bool, Error>
 
 pub fn update(game: &mut Game) -> Result<(), Error>
 {
+	game.play_events();
+	game.rend.animations.update();
+	game.rend.particles.update();
+
+	if game.error.is_some()
+	{
+		return Ok(());
+	}
+
 	game.tick_update()
 }
 
-pub fn draw(game: &mut Game)
+fn hud_text(title: &str, state: &TetrisState) -> String
 {
-	let state = &game.state;
-	let rend = &mut game.rend;
-	let fw = &mut game.fw;
+	let time = state.elapsed();
+
+	format!(
+		"{}\n\nTime   {}:{:05.2}\nLines  {}\nPieces {}\nPPS    {:.2}\nScore  {}\nLevel  {}",
+		title,
+		(time / 60.0) as u64,
+		time % 60.0,
+		state.lines_cleared,
+		state.pieces_placed,
+		state.pps(),
+		state.score,
+		state.level(),
+	)
+}
+
+pub fn draw(game: &mut Game) -> Result<(), Error>
+{
+	// Script errors are reported after the frame is drawn
+	let script = if game.error.is_none()
+	{
+		profile_api::call_draw(&mut game.state, &game.fw, &mut game.rend.overlay)
+	}
+	else
+	{
+		Ok(())
+	};
+
+	draw_state(
+		&mut game.rend,
+		game.fw.canvas,
+		&game.state,
+		game.error.as_deref(),
+	);
+
+	script
+}
 
+/// Draws the game in `state` to any render target, sized to `rend.win_dim`.
+pub fn draw_state<T: RenderTarget>(
+	rend: &mut drawer::Renderer, canvas: &mut Canvas<T>, state: &TetrisState, error: Option<&str>,
+)
+{
 	let fd = state.field_size;
 	let wd = rend.win_dim;
 
-	let drawer::size::ResizePattern {
-		threshold: _,
+	let queue = &state.piece_queue;
+	let largest = queue.iter().chain(&state.piece_swap).map(|p| p.dim);
+	let largest = largest.chain(Some(state.player_piece.dim)).max();
+	let hold = if flags::check(&state.flags, Flag::PieceHoldEnabled)
+	{
+		largest
+	}
+	else
+	{
+		None
+	};
+
+	let unit = if rend.integer_scaling
+	{
+		rend.tile_size()
+	}
+	else
+	{
+		1
+	};
+
+	let layout = drawer::layout::new_layout(
+		wd,
+		fd,
+		hold,
+		&queue.iter().map(|p| p.dim).collect::<Vec<_>>(),
+		unit,
+	);
+	let drawer::layout::Layout {
 		block_size,
 		field_rect,
-	} = drawer::size::new_resize(wd, fd);
-
-	let canvas = &mut fw.canvas;
-	let fc = rend.field_bg_color;
+		..
+	} = layout;
 
 	// Draw field
-	{
-		canvas.set_draw_color(fc);
-		canvas.fill_rect(field_rect).unwrap();
-	}
+	rend.draw_field(canvas, field_rect, fd, block_size);
 
-	// Draw field blocks
+	// Draw field blocks, lowered step by step while cleared lines collapse
 	{
-		let btex = &mut rend.block_texture;
 		let fbs = &state.field_blocks;
 		let fcs = &state.field_colors;
+		let fts = &state.field_tiles;
 
-		for (c, b) in fcs.iter().zip(fbs)
+		match rend.animations.collapse(fbs.len())
 		{
-			let r = Rect::new(
-				field_rect.x + b.x * block_size as i32,
-				field_rect.y + b.y * block_size as i32,
-				block_size,
-				block_size,
-			);
-
-			btex.set_color_mod(c.r, c.g, c.b);
-			canvas.copy(&btex, None, r).unwrap();
+			Some(c) =>
+			{
+				let offsets: Vec<i32> = fbs
+					.iter()
+					.map(|b| (c.offset(b.y) * block_size as f64).round() as i32)
+					.collect();
+
+				for (i, dy) in offsets.into_iter().enumerate()
+				{
+					let offset = field_rect.top_left().offset(0, -dy);
+					let r = i..i + 1;
+
+					rend.draw_blocks(
+						canvas,
+						offset,
+						block_size,
+						&fbs[r.clone()],
+						&fcs[r.clone()],
+						&fts[r],
+					);
+				}
+			},
+			None => rend.draw_blocks(canvas, field_rect.top_left(), block_size, fbs, fcs, fts),
 		}
 	}
 
-	// Draw player
+	// Draw player, unless the next piece is about to spawn
+	if !state.waiting()
 	{
 		let p = &state.player_piece;
 		let pl = state.player_pos;
 		let proj = state.player_proj;
 
 		let offset_x = field_rect.x + pl.x * block_size as i32;
-		let offset_y = field_rect.y + pl.y * block_size as i32;
-		rend.draw_blocks(
-			canvas,
-			Point::new(offset_x, offset_y),
-			block_size,
-			&p.blocks,
-			&p.colors,
-		);
 
-		let btex = &mut rend.block_texture;
-		btex.set_alpha_mod(127);
+		// The ghost goes below the piece, which covers it once landed
+		let ghost = Point::new(offset_x, field_rect.y + proj * block_size as i32);
+		rend.draw_ghost(canvas, ghost, block_size, &p.blocks, &p.colors, &p.tiles);
 
-		let offset_y = field_rect.y + proj * block_size as i32;
+		let offset_y = field_rect.y + pl.y * block_size as i32;
 		rend.draw_blocks(
 			canvas,
 			Point::new(offset_x, offset_y),
 			block_size,
 			&p.blocks,
 			&p.colors,
+			&p.tiles,
 		);
-
-		let btex = &mut rend.block_texture;
-		btex.set_alpha_mod(255);
 	}
 
+	// Draw animations
+	drawer::animation::draw_animations(rend, canvas, field_rect, block_size);
+
+	// Draw particles
+	drawer::particles::draw_particles(rend, canvas, field_rect, block_size);
+
 	// Draw piece view
 	{
 		let pvs = &state.piece_queue;
 		let idx = state.piece_queue_idx;
+		let ps = layout.preview_size;
 
-		let x = field_rect.x + field_rect.w + 10;
-		let mut y = field_rect.y;
+		let x = layout.queue_rect.x;
+		let mut y = layout.queue_rect.y;
 
 		for p in pvs[idx..].iter().chain(&pvs[..idx])
 		{
-			rend.draw_blocks(canvas, Point::new(x, y), block_size, &p.blocks, &p.colors);
-			y += (p.dim * block_size + 10) as i32;
+			rend.draw_blocks(canvas, Point::new(x, y), ps, &p.blocks, &p.colors, &p.tiles);
+			y += (p.dim * ps + ps / 2) as i32;
 		}
 	}
 
-	// Draw piece swap
+	// Draw piece swap, aligned with the field
 	if let Some(sp) = &state.piece_swap
 	{
 		let size = block_size * sp.dim;
-		let pos = Point::new(field_rect.x - size as i32 - 10, field_rect.y);
+		let pos = Point::new(layout.hold_rect.right() - size as i32, layout.hold_rect.y);
+
+		rend.draw_blocks(canvas, pos, block_size, &sp.blocks, &sp.colors, &sp.tiles);
+	}
+
+	// Draw HUD
+	if rend.hud
+	{
+		let text = hud_text(&rend.title, state);
+		rend.draw_hud(canvas, &text, layout.panel_rect, block_size);
+	}
+
+	// Draw script layer
+	{
+		let layer = std::mem::take(&mut rend.overlay);
+		drawer::overlay::draw_layer(rend, canvas, &layer, &layout);
+		rend.overlay = layer;
+	}
 
-		rend.draw_blocks(canvas, pos, block_size, &sp.blocks, &sp.colors);
+	// Draw error screen
+	if let Some(msg) = error
+	{
+		rend.draw_error(canvas, msg);
 	}
 }
@@ -4,52 +4,354 @@ use sdl2::{
 	image::LoadTexture,
 	pixels::Color,
 	rect::{Point, Rect},
-	render::{Texture, TextureCreator, WindowCanvas},
-	video::WindowContext,
+	render::{Canvas, RenderTarget, Texture, TextureCreator},
 };
 
 use super::Size;
-use crate::error::Error;
+use crate::{error::Error, settings::GhostMode};
 
-pub mod size;
+pub mod animation;
+pub mod capture;
+pub mod font;
+pub mod layout;
+pub mod overlay;
+pub mod particles;
+
+/// Width of the field border in pixels.
+const BORDER_WIDTH: i32 = 2;
+
+/// Filling of an area, either a single color or a gradient between two colors.
+#[derive(Debug, Clone, Copy)]
+pub enum Fill
+{
+	Solid(Color),
+	/// Changes from the first to the second color row by row, top to bottom.
+	Rows(Color, Color),
+	/// Changes from the first to the second color column by column, left to right.
+	Columns(Color, Color),
+}
+
+fn lerp(from: Color, to: Color, t: f64) -> Color
+{
+	let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+	Color::RGBA(
+		mix(from.r, to.r),
+		mix(from.g, to.g),
+		mix(from.b, to.b),
+		mix(from.a, to.a),
+	)
+}
+
+/// Source rectangle of a tile in a texture of `count` tiles of `size` pixels. Tiles past the end of
+/// the texture fall back to the first tile.
+pub fn tile_rect(tile: u32, size: u32, count: u32) -> Rect
+{
+	let tile = if tile < count { tile } else { 0 };
+
+	Rect::new((tile * size) as i32, 0, size, size)
+}
 
 pub struct Renderer<'a>
 {
 	pub win_dim: (u32, u32),
 
-	pub field_bg_color:     Color,
+	pub bg_color:           Color,
+	pub field_bg_color:     Fill,
 	pub field_border_color: Color,
+	pub grid_color:         Option<Color>,
+
+	/// Whether the stats are shown next to the field, headed by the profile name.
+	pub hud:   bool,
+	pub title: String,
+
+	/// Block tiles laid out horizontally, each as wide as the texture is high.
+	pub block_texture:   Texture<'a>,
+	pub tile_count:      u32,
+	pub ghost_tile:      Option<u32>,
+	pub ghost:           GhostMode,
+	/// Whether blocks are only drawn at whole multiples or fractions of the tile size.
+	pub integer_scaling: bool,
 
-	pub block_texture: Texture<'a>,
+	/// Glyph atlas of the embedded font.
+	pub font: Texture<'a>,
+
+	pub overlay: overlay::Layer,
+
+	pub animations: animation::Animations,
+	pub particles:  particles::Particles,
 }
 
-pub fn init_renderer<'a>(
-	tc: &'a TextureCreator<WindowContext>, win_dim: (u32, u32), block_bmp: &Path,
+pub fn init_renderer<'a, C>(
+	tc: &'a TextureCreator<C>, win_dim: (u32, u32), block_bmp: &Path, ghost_tile: Option<u32>,
 ) -> Result<Renderer<'a>, Error>
 {
 	let mut block_texture = tc.load_texture(block_bmp)?;
 	block_texture.set_blend_mode(sdl2::render::BlendMode::Blend);
 
+	let q = block_texture.query();
+	let tile_count = (q.width / q.height.max(1)).max(1);
+
+	let font = font::create_atlas(tc)?;
+
 	Ok(Renderer::<'a> {
 		win_dim,
 
-		field_bg_color: Color::BLACK,
+		bg_color: Color::GRAY,
+		field_bg_color: Fill::Solid(Color::BLACK),
 		field_border_color: Color::GRAY,
+		grid_color: None,
+
+		hud: true,
+		title: String::new(),
 
 		block_texture,
+		tile_count,
+		ghost_tile,
+		ghost: GhostMode::Tile,
+		integer_scaling: false,
+
+		font,
+
+		overlay: Vec::new(),
+
+		animations: animation::Animations::default(),
+		particles: particles::Particles::default(),
 	})
 }
 
 impl Renderer<'_>
 {
-	pub fn draw_blocks(
-		&mut self, canvas: &mut WindowCanvas, offset: Point, bs: u32, blocks: &[Point],
-		colors: &[Color],
+	/// Draws `text` with the bitmap font, its top left corner at `pos`.
+	pub fn draw_text<T: RenderTarget>(
+		&mut self, canvas: &mut Canvas<T>, pos: Point, text: &str, scale: u32, color: Color,
 	)
 	{
-		let btex = &mut self.block_texture;
+		font::draw_text(canvas, &mut self.font, pos, text, scale, color);
+	}
 
-		for (c, b) in colors.iter().zip(blocks)
+	/// Draws the stats to the bottom right of `panel`, shrinking them to fit its width.
+	pub fn draw_hud<T: RenderTarget>(
+		&mut self, canvas: &mut Canvas<T>, text: &str, panel: Rect, bs: u32,
+	)
+	{
+		let mut scale = (bs / 16).max(1);
+		while scale > 1 && font::text_size(text, scale).0 > panel.width()
+		{
+			scale -= 1;
+		}
+
+		let (w, h) = font::text_size(text, scale);
+
+		let x = panel.right() - w as i32;
+		let y = panel.bottom() - h as i32;
+
+		self.draw_text(
+			canvas,
+			Point::new(x.max(0), y.max(0)),
+			text,
+			scale,
+			Color::WHITE,
+		);
+	}
+
+	/// Covers the window with a message box showing `msg`.
+	pub fn draw_error<T: RenderTarget>(&mut self, canvas: &mut Canvas<T>, msg: &str)
+	{
+		const SCALE: u32 = 2;
+		const MARGIN: u32 = 20;
+
+		let (w, h) = self.win_dim;
+
+		canvas.set_draw_color(Color::RGBA(0, 0, 0, 220));
+		canvas.fill_rect(Rect::new(0, 0, w, h)).unwrap();
+
+		let columns = w.saturating_sub(2 * MARGIN) / (font::GLYPH_ADVANCE * SCALE);
+		let rows = h.saturating_sub(2 * MARGIN) / (font::LINE_ADVANCE * SCALE);
+
+		// The help is wrapped as well, narrow windows would cut it off
+		let help = font::wrap("Enter: continue   Escape: quit", columns as usize);
+
+		let mut lines = font::wrap(msg, columns as usize);
+		lines.truncate((rows as usize).saturating_sub(3 + help.len()));
+
+		let text = format!(
+			"Script error\n\n{}\n\n{}",
+			lines.join("\n"),
+			help.join("\n")
+		);
+
+		let pos = Point::new(MARGIN as i32, MARGIN as i32);
+		self.draw_text(canvas, pos, &text, SCALE, Color::WHITE);
+	}
+
+	/// Draws the field background with its grid lines and border.
+	pub fn draw_field<T: RenderTarget>(
+		&self, canvas: &mut Canvas<T>, field_rect: Rect, field_dim: Size, bs: u32,
+	)
+	{
+		let (w, h) = field_dim;
+		let bs = bs as i32;
+
+		let fill = |canvas: &mut Canvas<T>, c: Color, r: Rect| {
+			canvas.set_draw_color(c);
+			canvas.fill_rect(r).unwrap();
+		};
+
+		match self.field_bg_color
+		{
+			Fill::Solid(c) => fill(canvas, c, field_rect),
+			Fill::Rows(from, to) =>
+			{
+				for y in 0..h
+				{
+					let c = lerp(from, to, y as f64 / (h.max(2) - 1) as f64);
+					let r = Rect::new(
+						field_rect.x,
+						field_rect.y + y as i32 * bs,
+						field_rect.w as u32,
+						bs as u32,
+					);
+					fill(canvas, c, r);
+				}
+			},
+			Fill::Columns(from, to) =>
+			{
+				for x in 0..w
+				{
+					let c = lerp(from, to, x as f64 / (w.max(2) - 1) as f64);
+					let r = Rect::new(
+						field_rect.x + x as i32 * bs,
+						field_rect.y,
+						bs as u32,
+						field_rect.h as u32,
+					);
+					fill(canvas, c, r);
+				}
+			},
+		}
+
+		if let Some(c) = self.grid_color
+		{
+			canvas.set_draw_color(c);
+
+			for x in 1..w as i32
+			{
+				let x = field_rect.x + x * bs;
+				canvas.draw_line((x, field_rect.top()), (x, field_rect.bottom() - 1)).unwrap();
+			}
+
+			for y in 1..h as i32
+			{
+				let y = field_rect.y + y * bs;
+				canvas.draw_line((field_rect.left(), y), (field_rect.right() - 1, y)).unwrap();
+			}
+		}
+
+		canvas.set_draw_color(self.field_border_color);
+
+		for i in 1..=BORDER_WIDTH
+		{
+			let r = Rect::new(
+				field_rect.x - i,
+				field_rect.y - i,
+				field_rect.w as u32 + 2 * i as u32,
+				field_rect.h as u32 + 2 * i as u32,
+			);
+			canvas.draw_rect(r).unwrap();
+		}
+	}
+
+	/// Size of a tile in the block texture.
+	pub fn tile_size(&self) -> u32
+	{
+		self.block_texture.query().height
+	}
+
+	/// Source rectangle of a tile in the block texture.
+	pub fn tile_rect(&self, tile: u32) -> Rect
+	{
+		tile_rect(tile, self.tile_size(), self.tile_count)
+	}
+
+	/// Draws the ghost piece as chosen by `ghost`.
+	pub fn draw_ghost<T: RenderTarget>(
+		&mut self, canvas: &mut Canvas<T>, offset: Point, bs: u32, blocks: &[Point],
+		colors: &[Color], tiles: &[u32],
+	)
+	{
+		let rect = |b: &Point| {
+			Rect::new(
+				offset.x + b.x * bs as i32,
+				offset.y + b.y * bs as i32,
+				bs,
+				bs,
+			)
+		};
+
+		match (self.ghost, self.ghost_tile)
+		{
+			(GhostMode::Off, _) => (),
+
+			(GhostMode::Tile, Some(t)) =>
+			{
+				let tiles = vec![t; blocks.len()];
+				self.draw_blocks(canvas, offset, bs, blocks, colors, &tiles);
+			},
+
+			(GhostMode::Translucent, _) | (GhostMode::Tile, None) =>
+			{
+				self.block_texture.set_alpha_mod(127);
+				self.draw_blocks(canvas, offset, bs, blocks, colors, tiles);
+				self.block_texture.set_alpha_mod(255);
+			},
+
+			(GhostMode::Colored, _) =>
+			{
+				for (b, c) in blocks.iter().zip(colors)
+				{
+					canvas.set_draw_color(Color::RGBA(c.r, c.g, c.b, 96));
+					canvas.fill_rect(rect(b)).unwrap();
+				}
+			},
+
+			(GhostMode::Outline, _) =>
+			{
+				// Only the edges not shared with another block of the piece
+				for (b, c) in blocks.iter().zip(colors)
+				{
+					let r = rect(b);
+					let (l, t, rt, bt) = (r.left(), r.top(), r.right() - 1, r.bottom() - 1);
+					let free = |dx, dy| !blocks.contains(&Point::new(b.x + dx, b.y + dy));
+
+					canvas.set_draw_color(*c);
+
+					if free(0, -1)
+					{
+						canvas.draw_line((l, t), (rt, t)).unwrap();
+					}
+					if free(0, 1)
+					{
+						canvas.draw_line((l, bt), (rt, bt)).unwrap();
+					}
+					if free(-1, 0)
+					{
+						canvas.draw_line((l, t), (l, bt)).unwrap();
+					}
+					if free(1, 0)
+					{
+						canvas.draw_line((rt, t), (rt, bt)).unwrap();
+					}
+				}
+			},
+		}
+	}
+
+	pub fn draw_blocks<T: RenderTarget>(
+		&mut self, canvas: &mut Canvas<T>, offset: Point, bs: u32, blocks: &[Point],
+		colors: &[Color], tiles: &[u32],
+	)
+	{
+		for ((c, t), b) in colors.iter().zip(tiles).zip(blocks)
 		{
 			let r = Rect::new(
 				offset.x + b.x * bs as i32,
@@ -58,8 +360,11 @@ impl Renderer<'_>
 				bs,
 			);
 
+			let src = self.tile_rect(*t);
+			let btex = &mut self.block_texture;
+
 			btex.set_color_mod(c.r, c.g, c.b);
-			canvas.copy(&btex, None, r).unwrap();
+			canvas.copy(btex, src, r).unwrap();
 		}
 	}
 }
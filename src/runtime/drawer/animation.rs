@@ -0,0 +1,273 @@
+use std::time::{Duration, Instant};
+
+use sdl2::{
+	pixels::Color,
+	rect::{Point, Rect},
+	render::{Canvas, RenderTarget},
+};
+
+use super::Renderer;
+use crate::runtime::state::Clear;
+
+/// Durations of the animations, zero disabling one.
+#[derive(Debug, Clone, Copy)]
+pub struct Durations
+{
+	/// Cleared lines flash during the first half and the lines above collapse during the second.
+	pub line_clear: Duration,
+	pub lock_flash: Duration,
+	pub drop_trail: Duration,
+}
+
+impl Default for Durations
+{
+	fn default() -> Self
+	{
+		Durations {
+			line_clear: Duration::from_millis(250),
+			lock_flash: Duration::from_millis(120),
+			drop_trail: Duration::from_millis(150),
+		}
+	}
+}
+
+impl Durations
+{
+	pub const NONE: Durations = Durations {
+		line_clear: Duration::from_millis(0),
+		lock_flash: Duration::from_millis(0),
+		drop_trail: Duration::from_millis(0),
+	};
+}
+
+#[derive(Debug)]
+enum Kind
+{
+	LineClear
+	{
+		clear:     Clear,
+		/// Number of blocks on the field after the clear. The collapse stops once it changes.
+		field_len: usize,
+	},
+
+	LockFlash
+	{
+		blocks: Vec<Point>
+	},
+
+	/// Streaks above the blocks of a hard dropped piece, `distance` blocks long.
+	DropTrail
+	{
+		blocks:   Vec<Point>,
+		colors:   Vec<Color>,
+		distance: i32,
+	},
+}
+
+#[derive(Debug)]
+struct Effect
+{
+	kind:     Kind,
+	start:    Instant,
+	duration: Duration,
+}
+
+impl Effect
+{
+	/// Progress of the effect from 0 to 1.
+	fn progress(&self) -> f64
+	{
+		let t = self.start.elapsed().as_secs_f64() / self.duration.as_secs_f64();
+		t.min(1.0)
+	}
+}
+
+/// Lines collapsing onto the rows below them.
+pub struct Collapse<'a>
+{
+	rows:     &'a [i32],
+	progress: f64,
+}
+
+impl Collapse<'_>
+{
+	/// Distance in blocks the blocks now at row `y` still have to fall.
+	pub fn offset(&self, y: i32) -> f64
+	{
+		let below = |r: i32| self.rows.iter().filter(|l| **l > r).count() as i32;
+
+		// Find the row the block was on before the lines were removed
+		let shift = (y - self.rows.len() as i32..=y)
+			.rev()
+			.find(|r| !self.rows.contains(r) && r + below(*r) == y)
+			.map_or(0, |r| y - r);
+
+		shift as f64 * (1.0 - self.progress)
+	}
+}
+
+/// Effects played on top of the field.
+#[derive(Debug, Default)]
+pub struct Animations
+{
+	pub durations: Durations,
+
+	effects: Vec<Effect>,
+}
+
+impl Animations
+{
+	fn push(&mut self, kind: Kind, duration: Duration)
+	{
+		if duration > Duration::from_millis(0)
+		{
+			self.effects.push(Effect {
+				kind,
+				start: Instant::now(),
+				duration,
+			});
+		}
+	}
+
+	pub fn line_clear(&mut self, clear: Clear, field_len: usize)
+	{
+		self.push(
+			Kind::LineClear { clear, field_len },
+			self.durations.line_clear,
+		);
+	}
+
+	pub fn lock_flash(&mut self, blocks: Vec<Point>)
+	{
+		self.push(Kind::LockFlash { blocks }, self.durations.lock_flash);
+	}
+
+	pub fn drop_trail(&mut self, blocks: Vec<Point>, colors: Vec<Color>, distance: i32)
+	{
+		if distance > 0
+		{
+			let kind = Kind::DropTrail {
+				blocks,
+				colors,
+				distance,
+			};
+			self.push(kind, self.durations.drop_trail);
+		}
+	}
+
+	/// Removes the finished effects.
+	pub fn update(&mut self)
+	{
+		self.effects.retain(|e| e.start.elapsed() < e.duration);
+	}
+
+	/// The lines collapsing after the latest line clear, if the field didn't change since.
+	pub fn collapse(&self, field_len: usize) -> Option<Collapse<'_>>
+	{
+		self.effects.iter().rev().find_map(|e| match &e.kind
+		{
+			Kind::LineClear {
+				clear,
+				field_len: l,
+			} if *l == field_len => Some(Collapse {
+				rows:     &clear.rows,
+				progress: (e.progress() * 2.0 - 1.0).max(0.0),
+			}),
+			_ => None,
+		})
+	}
+}
+
+fn block_rect(field_rect: Rect, bs: u32, b: Point) -> Rect
+{
+	Rect::new(
+		field_rect.x + b.x * bs as i32,
+		field_rect.y + b.y * bs as i32,
+		bs,
+		bs,
+	)
+}
+
+/// Draws the running effects over the field.
+pub fn draw_animations<T: RenderTarget>(
+	rend: &mut Renderer, canvas: &mut Canvas<T>, field_rect: Rect, bs: u32,
+)
+{
+	let animations = std::mem::take(&mut rend.animations);
+
+	for e in &animations.effects
+	{
+		let t = e.progress();
+
+		match &e.kind
+		{
+			Kind::LineClear { clear, .. } =>
+			{
+				// The removed blocks only show while flashing, the collapse follows
+				if t >= 0.5
+				{
+					continue;
+				}
+
+				rend.draw_blocks(
+					canvas,
+					field_rect.top_left(),
+					bs,
+					&clear.blocks,
+					&clear.colors,
+					&clear.tiles,
+				);
+
+				let alpha = (255.0 * (1.0 - t * 2.0)) as u8;
+				canvas.set_draw_color(Color::RGBA(255, 255, 255, alpha));
+
+				for y in &clear.rows
+				{
+					let r = block_rect(field_rect, bs, Point::new(0, *y));
+					canvas.fill_rect(Rect::new(r.x, r.y, field_rect.width(), bs)).unwrap();
+				}
+			},
+
+			Kind::LockFlash { blocks } =>
+			{
+				let alpha = (180.0 * (1.0 - t)) as u8;
+				canvas.set_draw_color(Color::RGBA(255, 255, 255, alpha));
+
+				for b in blocks
+				{
+					canvas.fill_rect(block_rect(field_rect, bs, *b)).unwrap();
+				}
+			},
+
+			Kind::DropTrail {
+				blocks,
+				colors,
+				distance,
+			} =>
+			{
+				for (b, c) in blocks.iter().zip(colors)
+				{
+					// Only the top block of every column leaves a trail
+					if blocks.contains(&Point::new(b.x, b.y - 1))
+					{
+						continue;
+					}
+
+					// Fades out towards its top and over time
+					for i in 1..=*distance
+					{
+						let fade = (1.0 - t) * (1.0 - i as f64 / (*distance + 1) as f64);
+						let alpha = (120.0 * fade) as u8;
+
+						canvas.set_draw_color(Color::RGBA(c.r, c.g, c.b, alpha));
+						canvas
+							.fill_rect(block_rect(field_rect, bs, Point::new(b.x, b.y - i)))
+							.unwrap();
+					}
+				}
+			},
+		}
+	}
+
+	rend.animations = animations;
+}
@@ -0,0 +1,108 @@
+use std::{
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::info;
+use sdl2::{
+	image::{LoadTexture, SaveSurface},
+	pixels::{Color, PixelFormatEnum},
+	rect::{Point, Rect},
+	render::WindowCanvas,
+	surface::Surface,
+};
+
+use super::{tile_rect, Size};
+use crate::{error::Error, settings::ImageFormat, storage};
+
+/// Directory inside the user data directory holding screenshots and exported fields.
+const SCREENSHOTS_DIR: &str = "screenshots";
+
+/// Returns a new file in the screenshots directory, named after `prefix` and the current time.
+pub fn screenshot_file(prefix: &str, format: ImageFormat) -> Result<PathBuf, Error>
+{
+	let dir = storage::data_dir().join(SCREENSHOTS_DIR);
+	std::fs::create_dir_all(&dir)?;
+
+	let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+
+	Ok(dir.join(format!("{}-{}.{}", prefix, time, format.extension())))
+}
+
+fn save_surface(surface: &Surface, file: &Path, format: ImageFormat) -> Result<(), Error>
+{
+	info!("Saving image \"{}\".", file.display());
+
+	match format
+	{
+		ImageFormat::Png => surface.save(file)?,
+		ImageFormat::Bmp => surface.save_bmp(file)?,
+	}
+
+	Ok(())
+}
+
+/// Saves what was drawn to the canvas since it was last presented.
+pub fn save_frame(canvas: &WindowCanvas, file: &Path, format: ImageFormat) -> Result<(), Error>
+{
+	let pf = PixelFormatEnum::ARGB8888;
+	let (w, h) = canvas.output_size()?;
+
+	let mut pixels = canvas.read_pixels(None, pf)?;
+	let surface = Surface::from_data(
+		&mut pixels,
+		w,
+		h,
+		pf.byte_size_of_pixels(w as usize) as u32,
+		pf,
+	)?;
+
+	save_surface(&surface, file, format)
+}
+
+/// Blocks of a field with the color and texture tile of each.
+pub struct FieldImage<'a>
+{
+	pub field_dim: Size,
+	pub blocks:    &'a [Point],
+	pub colors:    &'a [Color],
+	pub tiles:     &'a [u32],
+
+	pub background: Color,
+}
+
+/// Renders a field to an image file with blocks of `block_size` pixels taken from the tilesheet
+/// `texture`. Needs no window, only the field state.
+pub fn export_field(
+	field: &FieldImage, texture: &Path, block_size: u32, file: &Path, format: ImageFormat,
+) -> Result<(), Error>
+{
+	let (w, h) = field.field_dim;
+	let surface = Surface::new(w * block_size, h * block_size, PixelFormatEnum::ARGB8888)?;
+
+	let mut canvas = surface.into_canvas()?;
+	canvas.set_draw_color(field.background);
+	canvas.clear();
+
+	let tc = canvas.texture_creator();
+	let mut tex = tc.load_texture(texture)?;
+
+	let size = tex.query().height;
+	let count = (tex.query().width / size.max(1)).max(1);
+
+	for ((b, c), t) in field.blocks.iter().zip(field.colors).zip(field.tiles)
+	{
+		let r = Rect::new(
+			b.x * block_size as i32,
+			b.y * block_size as i32,
+			block_size,
+			block_size,
+		);
+
+		tex.set_color_mod(c.r, c.g, c.b);
+		canvas.copy(&tex, tile_rect(*t, size, count), r)?;
+	}
+
+	canvas.present();
+	save_surface(&canvas.into_surface(), file, format)
+}
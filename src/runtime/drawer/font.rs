@@ -0,0 +1,235 @@
+use sdl2::{
+	pixels::{Color, PixelFormatEnum},
+	rect::{Point, Rect},
+	render::{Canvas, RenderTarget, Texture, TextureCreator},
+	surface::Surface,
+};
+
+use crate::error::Error;
+
+// -----------------------------------------------------------------------------
+// Embedded 5x7 Font
+// -----------------------------------------------------------------------------
+
+pub const GLYPH_WIDTH: u32 = 5;
+pub const GLYPH_HEIGHT: u32 = 7;
+
+/// Horizontal advance of a glyph in font pixels (glyph plus one column spacing).
+pub const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+/// Vertical advance of a line in font pixels (glyph plus one row spacing).
+pub const LINE_ADVANCE: u32 = GLYPH_HEIGHT + 1;
+
+/// Printable ASCII glyphs starting at `' '`. Each glyph is stored column by column, the lowest bit
+/// being the top row.
+#[rustfmt::skip]
+const GLYPHS: [[u8; 5]; 95] = [
+	[0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+	[0x00, 0x00, 0x5F, 0x00, 0x00], // !
+	[0x00, 0x07, 0x00, 0x07, 0x00], // "
+	[0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+	[0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+	[0x23, 0x13, 0x08, 0x64, 0x62], // %
+	[0x36, 0x49, 0x55, 0x22, 0x50], // &
+	[0x00, 0x05, 0x03, 0x00, 0x00], // '
+	[0x00, 0x1C, 0x22, 0x41, 0x00], // (
+	[0x00, 0x41, 0x22, 0x1C, 0x00], // )
+	[0x08, 0x2A, 0x1C, 0x2A, 0x08], // *
+	[0x08, 0x08, 0x3E, 0x08, 0x08], // +
+	[0x00, 0x50, 0x30, 0x00, 0x00], // ,
+	[0x08, 0x08, 0x08, 0x08, 0x08], // -
+	[0x00, 0x60, 0x60, 0x00, 0x00], // .
+	[0x20, 0x10, 0x08, 0x04, 0x02], // /
+	[0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+	[0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+	[0x42, 0x61, 0x51, 0x49, 0x46], // 2
+	[0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+	[0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+	[0x27, 0x45, 0x45, 0x45, 0x39], // 5
+	[0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+	[0x01, 0x71, 0x09, 0x05, 0x03], // 7
+	[0x36, 0x49, 0x49, 0x49, 0x36], // 8
+	[0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+	[0x00, 0x36, 0x36, 0x00, 0x00], // :
+	[0x00, 0x56, 0x36, 0x00, 0x00], // ;
+	[0x00, 0x08, 0x14, 0x22, 0x41], // <
+	[0x14, 0x14, 0x14, 0x14, 0x14], // =
+	[0x41, 0x22, 0x14, 0x08, 0x00], // >
+	[0x02, 0x01, 0x51, 0x09, 0x06], // ?
+	[0x32, 0x49, 0x79, 0x41, 0x3E], // @
+	[0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+	[0x7F, 0x49, 0x49, 0x49, 0x36], // B
+	[0x3E, 0x41, 0x41, 0x41, 0x22], // C
+	[0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+	[0x7F, 0x49, 0x49, 0x49, 0x41], // E
+	[0x7F, 0x09, 0x09, 0x01, 0x01], // F
+	[0x3E, 0x41, 0x41, 0x51, 0x32], // G
+	[0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+	[0x00, 0x41, 0x7F, 0x41, 0x00], // I
+	[0x20, 0x40, 0x41, 0x3F, 0x01], // J
+	[0x7F, 0x08, 0x14, 0x22, 0x41], // K
+	[0x7F, 0x40, 0x40, 0x40, 0x40], // L
+	[0x7F, 0x02, 0x04, 0x02, 0x7F], // M
+	[0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+	[0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+	[0x7F, 0x09, 0x09, 0x09, 0x06], // P
+	[0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+	[0x7F, 0x09, 0x19, 0x29, 0x46], // R
+	[0x46, 0x49, 0x49, 0x49, 0x31], // S
+	[0x01, 0x01, 0x7F, 0x01, 0x01], // T
+	[0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+	[0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+	[0x7F, 0x20, 0x18, 0x20, 0x7F], // W
+	[0x63, 0x14, 0x08, 0x14, 0x63], // X
+	[0x03, 0x04, 0x78, 0x04, 0x03], // Y
+	[0x61, 0x51, 0x49, 0x45, 0x43], // Z
+	[0x00, 0x00, 0x7F, 0x41, 0x41], // [
+	[0x02, 0x04, 0x08, 0x10, 0x20], // \
+	[0x41, 0x41, 0x7F, 0x00, 0x00], // ]
+	[0x04, 0x02, 0x01, 0x02, 0x04], // ^
+	[0x40, 0x40, 0x40, 0x40, 0x40], // _
+	[0x00, 0x01, 0x02, 0x04, 0x00], // `
+	[0x20, 0x54, 0x54, 0x54, 0x78], // a
+	[0x7F, 0x48, 0x44, 0x44, 0x38], // b
+	[0x38, 0x44, 0x44, 0x44, 0x20], // c
+	[0x38, 0x44, 0x44, 0x48, 0x7F], // d
+	[0x38, 0x54, 0x54, 0x54, 0x18], // e
+	[0x08, 0x7E, 0x09, 0x01, 0x02], // f
+	[0x08, 0x14, 0x54, 0x54, 0x3C], // g
+	[0x7F, 0x08, 0x04, 0x04, 0x78], // h
+	[0x00, 0x44, 0x7D, 0x40, 0x00], // i
+	[0x20, 0x40, 0x44, 0x3D, 0x00], // j
+	[0x00, 0x7F, 0x10, 0x28, 0x44], // k
+	[0x00, 0x41, 0x7F, 0x40, 0x00], // l
+	[0x7C, 0x04, 0x18, 0x04, 0x78], // m
+	[0x7C, 0x08, 0x04, 0x04, 0x78], // n
+	[0x38, 0x44, 0x44, 0x44, 0x38], // o
+	[0x7C, 0x14, 0x14, 0x14, 0x08], // p
+	[0x08, 0x14, 0x14, 0x18, 0x7C], // q
+	[0x7C, 0x08, 0x04, 0x04, 0x08], // r
+	[0x48, 0x54, 0x54, 0x54, 0x20], // s
+	[0x04, 0x3F, 0x44, 0x40, 0x20], // t
+	[0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+	[0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+	[0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+	[0x44, 0x28, 0x10, 0x28, 0x44], // x
+	[0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+	[0x44, 0x64, 0x54, 0x4C, 0x44], // z
+	[0x00, 0x08, 0x36, 0x41, 0x00], // {
+	[0x00, 0x00, 0x7F, 0x00, 0x00], // |
+	[0x00, 0x41, 0x36, 0x08, 0x00], // }
+	[0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+// -----------------------------------------------------------------------------
+// Font Atlas
+// -----------------------------------------------------------------------------
+
+/// Renders every glyph side by side into a white texture, one `GLYPH_ADVANCE` wide cell per glyph.
+/// Text is drawn by copying cells and tinting them with the texture's color modulation.
+pub fn create_atlas<C>(tc: &TextureCreator<C>) -> Result<Texture<'_>, Error>
+{
+	let (w, h) = (GLYPHS.len() as u32 * GLYPH_ADVANCE, LINE_ADVANCE);
+	let mut surface = Surface::new(w, h, PixelFormatEnum::ARGB8888)?;
+
+	surface.with_lock_mut(|pixels| {
+		let pitch = (w * 4) as usize;
+
+		for (i, g) in GLYPHS.iter().enumerate()
+		{
+			for (gx, bits) in g.iter().enumerate()
+			{
+				for gy in 0..GLYPH_HEIGHT as usize
+				{
+					if bits & (1 << gy) != 0
+					{
+						let x = i * GLYPH_ADVANCE as usize + gx;
+						let o = gy * pitch + x * 4;
+						pixels[o..o + 4].copy_from_slice(&[0xFF; 4]);
+					}
+				}
+			}
+		}
+	});
+
+	let mut atlas = tc.create_texture_from_surface(&surface)?;
+	atlas.set_blend_mode(sdl2::render::BlendMode::Blend);
+
+	Ok(atlas)
+}
+
+fn glyph_rect(c: char) -> Rect
+{
+	let i = c as u32;
+	let i = if (0x20..0x7F).contains(&i)
+	{
+		i
+	}
+	else
+	{
+		b'?' as u32
+	} - 0x20;
+
+	Rect::new((i * GLYPH_ADVANCE) as i32, 0, GLYPH_WIDTH, GLYPH_HEIGHT)
+}
+
+/// Draws text using the atlas created by `create_atlas`. Every font pixel is drawn as a
+/// `scale` sized square.
+pub fn draw_text<T: RenderTarget>(
+	canvas: &mut Canvas<T>, atlas: &mut Texture, pos: Point, text: &str, scale: u32, color: Color,
+)
+{
+	let scale = scale.max(1);
+
+	atlas.set_color_mod(color.r, color.g, color.b);
+	atlas.set_alpha_mod(color.a);
+
+	for (row, line) in text.lines().enumerate()
+	{
+		let y = pos.y + (row as u32 * LINE_ADVANCE * scale) as i32;
+
+		for (col, c) in line.chars().enumerate()
+		{
+			let x = pos.x + (col as u32 * GLYPH_ADVANCE * scale) as i32;
+			let dst = Rect::new(x, y, GLYPH_WIDTH * scale, GLYPH_HEIGHT * scale);
+
+			canvas.copy(atlas, glyph_rect(c), dst).unwrap();
+		}
+	}
+}
+
+/// Size of the text in pixels when drawn at `scale`.
+pub fn text_size(text: &str, scale: u32) -> (u32, u32)
+{
+	let columns = text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as u32;
+	let rows = text.lines().count() as u32;
+
+	(
+		columns * GLYPH_ADVANCE * scale.max(1),
+		rows * LINE_ADVANCE * scale.max(1),
+	)
+}
+
+/// Splits text into lines of at most `columns` characters. Tabs are expanded to two spaces.
+pub fn wrap(text: &str, columns: usize) -> Vec<String>
+{
+	let columns = columns.max(1);
+	let mut lines = Vec::new();
+
+	for line in text.lines()
+	{
+		let chars: Vec<char> = line.replace('\t', "  ").chars().collect();
+
+		if chars.is_empty()
+		{
+			lines.push(String::new());
+		}
+
+		for chunk in chars.chunks(columns)
+		{
+			lines.push(chunk.iter().collect());
+		}
+	}
+
+	lines
+}
@@ -0,0 +1,140 @@
+use sdl2::rect::Rect;
+
+use super::Size;
+
+#[cfg(test)]
+mod tests;
+
+/// Width in blocks of the panels on either side of the field. The left one holds the hold box,
+/// the HUD and what scripts draw relative to "panel".
+const SIDE_BLOCKS: f64 = 6.0;
+
+/// Space between the field, the panels and the window edge, in blocks.
+const GAP: f64 = 0.5;
+
+/// Size of the queued pieces relative to the field blocks.
+const PREVIEW_SCALE: f64 = 0.6;
+
+/// Areas of the game screen, sized together to fit the window.
+#[derive(Debug, Clone, Copy)]
+pub struct Layout
+{
+	pub block_size: u32,
+	pub field_rect: Rect,
+
+	/// Box of the held piece, empty if holding is disabled.
+	pub hold_rect:  Rect,
+	/// Area below the hold box, for the HUD and script drawing relative to "panel".
+	pub panel_rect: Rect,
+
+	pub queue_rect:   Rect,
+	/// Block size of the queued pieces, smaller than the field blocks.
+	pub preview_size: u32,
+}
+
+/// Rounds a block size down to a whole multiple of `unit`, or to the largest divisor of `unit` if
+/// it is smaller.
+fn snap(size: u32, unit: u32) -> u32
+{
+	if unit <= 1
+	{
+		size
+	}
+	else if size >= unit
+	{
+		size - size % unit
+	}
+	else
+	{
+		(1..=size.max(1)).rev().find(|n| unit.is_multiple_of(*n)).unwrap_or(1)
+	}
+}
+
+/// Lays out the game for a window of `win_dim` pixels.
+///
+/// # Arguments
+///
+/// * `hold_dim` Size of the largest piece that can be held, `None` if holding is disabled
+/// * `queue_dims` Sizes of the queued pieces
+/// * `unit` Block sizes are snapped to whole multiples or fractions of it, 1 not snapping them
+pub fn new_layout(
+	win_dim: (u32, u32), field_dim: Size, hold_dim: Option<u32>, queue_dims: &[u32], unit: u32,
+) -> Layout
+{
+	let queue_max = queue_dims.iter().copied().max().unwrap_or(0) as f64;
+	let hold_max = hold_dim.unwrap_or(0) as f64;
+
+	let side = SIDE_BLOCKS.max(queue_max * PREVIEW_SCALE + GAP).max(hold_max + GAP);
+
+	// Blocks across and down, the field being centered between two equally wide panels
+	let cols = field_dim.0 as f64 + 2.0 * (side + GAP);
+	let rows = field_dim.1 as f64 + 2.0 * GAP;
+
+	let block_size = (win_dim.0 as f64 / cols).min(win_dim.1 as f64 / rows).floor().max(1.0) as u32;
+	let block_size = snap(block_size, unit);
+	let bs = block_size as f64;
+	let px = |blocks: f64| (blocks * bs).round() as i32;
+
+	let field_rect = {
+		let (w, h) = (block_size * field_dim.0, block_size * field_dim.1);
+		Rect::new(
+			(win_dim.0 as i32 - w as i32) / 2,
+			(win_dim.1 as i32 - h as i32) / 2,
+			w,
+			h,
+		)
+	};
+
+	let gap = px(GAP);
+	let side_w = px(side - GAP).max(1) as u32;
+
+	let hold_rect = Rect::new(
+		field_rect.x - gap - side_w as i32,
+		field_rect.y,
+		side_w,
+		(hold_max * bs) as u32,
+	);
+
+	let panel_y = if hold_dim.is_some()
+	{
+		hold_rect.bottom() + gap
+	}
+	else
+	{
+		field_rect.y
+	};
+	let panel_rect = Rect::new(
+		hold_rect.x,
+		panel_y,
+		side_w,
+		(field_rect.bottom() - panel_y).max(1) as u32,
+	);
+
+	let queue_rect = Rect::new(
+		field_rect.right() + gap,
+		field_rect.y,
+		side_w,
+		field_rect.height(),
+	);
+
+	// Shrink the previews further if the queue is longer than the field
+	let queue_len =
+		queue_dims.iter().sum::<u32>() as f64 + GAP * queue_dims.len().saturating_sub(1) as f64;
+	let preview_size = if queue_len > 0.0
+	{
+		(bs * PREVIEW_SCALE).min(queue_rect.height() as f64 / queue_len)
+	}
+	else
+	{
+		bs * PREVIEW_SCALE
+	};
+
+	Layout {
+		block_size,
+		field_rect,
+		hold_rect,
+		panel_rect,
+		queue_rect,
+		preview_size: snap(preview_size.floor().max(1.0) as u32, unit),
+	}
+}
@@ -0,0 +1,92 @@
+use super::{new_layout, snap};
+
+#[test]
+fn snap_without_unit()
+{
+	assert_eq!(snap(37, 1), 37);
+	assert_eq!(snap(37, 0), 37);
+}
+
+#[test]
+fn snap_to_multiples()
+{
+	assert_eq!(snap(16, 16), 16);
+	assert_eq!(snap(37, 16), 32);
+	assert_eq!(snap(63, 16), 48);
+}
+
+#[test]
+fn snap_to_fractions()
+{
+	assert_eq!(snap(15, 16), 8);
+	assert_eq!(snap(8, 16), 8);
+	assert_eq!(snap(6, 16), 4);
+	assert_eq!(snap(7, 16), 4);
+	assert_eq!(snap(12, 16), 8);
+	assert_eq!(snap(10, 12), 6);
+	assert_eq!(snap(5, 12), 4);
+	assert_eq!(snap(0, 16), 1);
+}
+
+#[test]
+fn field_is_centered()
+{
+	let l = new_layout((1080, 720), (10, 20), Some(4), &[4, 4, 4], 1);
+
+	assert_eq!(l.block_size, 34);
+	assert_eq!(l.field_rect.size(), (340, 680));
+	assert_eq!(l.field_rect.center(), (540, 360).into());
+}
+
+#[test]
+fn panels_fit_around_the_field()
+{
+	let l = new_layout((1080, 720), (10, 20), Some(4), &[4, 4, 4], 1);
+
+	assert!(l.hold_rect.right() < l.field_rect.left());
+	assert!(l.queue_rect.left() > l.field_rect.right());
+	assert!(l.hold_rect.left() >= 0);
+	assert!(l.queue_rect.right() <= 1080);
+
+	assert!(l.panel_rect.top() > l.hold_rect.bottom());
+	assert_eq!(l.panel_rect.bottom(), l.field_rect.bottom());
+
+	assert!(l.preview_size < l.block_size);
+}
+
+#[test]
+fn panel_starts_at_the_field_without_hold()
+{
+	let l = new_layout((1080, 720), (10, 20), None, &[4], 1);
+
+	assert_eq!(l.panel_rect.top(), l.field_rect.top());
+}
+
+#[test]
+fn long_queues_shrink_the_previews()
+{
+	let short = new_layout((1080, 720), (10, 20), None, &[4], 1);
+	let long = new_layout((1080, 720), (10, 20), None, &[4; 12], 1);
+
+	assert!(long.preview_size < short.preview_size);
+	assert!(long.preview_size * (4 * 12) <= long.queue_rect.height());
+}
+
+#[test]
+fn integer_scaling_snaps_every_block_size()
+{
+	let l = new_layout((1080, 720), (10, 20), Some(4), &[4, 4, 4], 16);
+
+	assert_eq!(l.block_size, 32);
+	assert_eq!(l.field_rect.size(), (320, 640));
+	assert_eq!(l.preview_size, 16);
+}
+
+#[test]
+fn integer_scaling_below_the_unit()
+{
+	let l = new_layout((320, 240), (10, 20), None, &[4], 16);
+
+	assert_eq!(l.block_size, 8);
+	assert_eq!(l.preview_size, 4);
+}
@@ -0,0 +1,223 @@
+use sdl2::{
+	pixels::Color,
+	rect::{Point, Rect},
+	render::{Canvas, RenderTarget},
+};
+
+use super::{font, layout::Layout, Renderer};
+use crate::runtime::Piece;
+
+// -----------------------------------------------------------------------------
+// Script Draw Commands
+// -----------------------------------------------------------------------------
+
+/// Coordinate space of a draw command. Field coordinates are measured in blocks from the top
+/// left corner of the field, window coordinates in pixels from the top left corner of the window.
+#[derive(Debug, Clone, Copy)]
+pub enum Anchor
+{
+	Field,
+	/// Blocks from the top left corner of the panel left of the field, below the hold box. The
+	/// panel is at least five blocks wide and ends at the bottom of the field, the HUD taking its
+	/// bottom right corner.
+	Panel,
+	Window,
+}
+
+#[derive(Debug)]
+pub enum Command
+{
+	Text
+	{
+		anchor: Anchor,
+		x:      f64,
+		y:      f64,
+		size:   f64,
+		text:   String,
+		color:  Color,
+	},
+
+	Rect
+	{
+		anchor: Anchor,
+		x:      f64,
+		y:      f64,
+		w:      f64,
+		h:      f64,
+		color:  Color,
+	},
+
+	Piece
+	{
+		anchor: Anchor,
+		x:      f64,
+		y:      f64,
+		size:   f64,
+		piece:  Piece,
+	},
+
+	Progress
+	{
+		anchor:     Anchor,
+		x:          f64,
+		y:          f64,
+		w:          f64,
+		h:          f64,
+		value:      f64,
+		color:      Color,
+		background: Color,
+	},
+}
+
+/// Commands issued by the profile during the current frame.
+pub type Layer = Vec<Command>;
+
+struct Space
+{
+	origin: Point,
+	unit:   f64,
+}
+
+impl Space
+{
+	fn new(anchor: Anchor, layout: &Layout) -> Self
+	{
+		match anchor
+		{
+			Anchor::Field => Space {
+				origin: layout.field_rect.top_left(),
+				unit:   layout.block_size as f64,
+			},
+			Anchor::Panel => Space {
+				origin: layout.panel_rect.top_left(),
+				unit:   layout.block_size as f64,
+			},
+			Anchor::Window => Space {
+				origin: Point::new(0, 0),
+				unit:   1.0,
+			},
+		}
+	}
+
+	fn point(&self, x: f64, y: f64) -> Point
+	{
+		Point::new(
+			self.origin.x + (x * self.unit).round() as i32,
+			self.origin.y + (y * self.unit).round() as i32,
+		)
+	}
+
+	fn length(&self, l: f64) -> u32
+	{
+		(l * self.unit).round().max(0.0) as u32
+	}
+
+	fn rect(&self, x: f64, y: f64, w: f64, h: f64) -> Option<Rect>
+	{
+		let (w, h) = (self.length(w), self.length(h));
+
+		if w == 0 || h == 0
+		{
+			return None;
+		}
+
+		let p = self.point(x, y);
+		Some(Rect::new(p.x, p.y, w, h))
+	}
+}
+
+/// Draws the commands of a layer on top of the already drawn game.
+pub fn draw_layer<T: RenderTarget>(
+	rend: &mut Renderer, canvas: &mut Canvas<T>, layer: &[Command], layout: &Layout,
+)
+{
+	for cmd in layer
+	{
+		match cmd
+		{
+			Command::Text {
+				anchor,
+				x,
+				y,
+				size,
+				text,
+				color,
+			} =>
+			{
+				let s = Space::new(*anchor, layout);
+				let scale = s.length(*size) / font::LINE_ADVANCE;
+
+				rend.draw_text(canvas, s.point(*x, *y), text, scale, *color);
+			},
+
+			Command::Rect {
+				anchor,
+				x,
+				y,
+				w,
+				h,
+				color,
+			} =>
+			{
+				let s = Space::new(*anchor, layout);
+
+				if let Some(r) = s.rect(*x, *y, *w, *h)
+				{
+					canvas.set_draw_color(*color);
+					canvas.fill_rect(r).unwrap();
+				}
+			},
+
+			Command::Piece {
+				anchor,
+				x,
+				y,
+				size,
+				piece,
+			} =>
+			{
+				let s = Space::new(*anchor, layout);
+				let bs = s.length(*size);
+
+				if bs > 0
+				{
+					rend.draw_blocks(
+						canvas,
+						s.point(*x, *y),
+						bs,
+						&piece.blocks,
+						&piece.colors,
+						&piece.tiles,
+					);
+				}
+			},
+
+			Command::Progress {
+				anchor,
+				x,
+				y,
+				w,
+				h,
+				value,
+				color,
+				background,
+			} =>
+			{
+				let s = Space::new(*anchor, layout);
+				let value = value.clamp(0.0, 1.0);
+
+				if let Some(r) = s.rect(*x, *y, *w, *h)
+				{
+					canvas.set_draw_color(*background);
+					canvas.fill_rect(r).unwrap();
+				}
+
+				if let Some(r) = s.rect(*x, *y, *w * value, *h)
+				{
+					canvas.set_draw_color(*color);
+					canvas.fill_rect(r).unwrap();
+				}
+			},
+		}
+	}
+}
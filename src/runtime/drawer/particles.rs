@@ -0,0 +1,221 @@
+use std::{f64::consts::PI, time::Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sdl2::{
+	pixels::Color,
+	rect::{Point, Rect},
+	render::{Canvas, RenderTarget},
+};
+
+use super::{lerp, Renderer};
+
+/// Particles alive at once, further ones aren't emitted.
+const MAX_PARTICLES: usize = 2000;
+
+/// How a particle is drawn.
+#[derive(Debug, Clone, Copy)]
+pub enum Sprite
+{
+	/// A tile of the block texture.
+	Tile(u32),
+	/// A filled square.
+	Square,
+}
+
+/// Describes the particles emitted at once. Distances are measured in blocks and times in seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct Emitter
+{
+	pub count:    usize,
+	pub lifetime: (f64, f64),
+	pub speed:    (f64, f64),
+	/// Range of directions in radians, 0 pointing right and PI / 2 down.
+	pub angle:    (f64, f64),
+	pub gravity:  f64,
+	pub size:     f64,
+	/// Colors at the start and the end of a particle's life, faded in between.
+	pub colors:   (Color, Color),
+	pub sprite:   Sprite,
+}
+
+impl Emitter
+{
+	/// Sparks flying out in every direction.
+	pub fn burst(color: Color, count: usize) -> Self
+	{
+		Emitter {
+			count,
+			lifetime: (0.3, 0.7),
+			speed: (3.0, 9.0),
+			angle: (0.0, 2.0 * PI),
+			gravity: 12.0,
+			size: 0.2,
+			colors: (color, Color::RGBA(color.r, color.g, color.b, 0)),
+			sprite: Sprite::Square,
+		}
+	}
+
+	/// Fragments of a block thrown upwards and falling off the field.
+	pub fn shatter(color: Color, tile: u32) -> Self
+	{
+		Emitter {
+			count:    2,
+			lifetime: (0.4, 0.8),
+			speed:    (2.0, 6.0),
+			angle:    (1.25 * PI, 1.75 * PI),
+			gravity:  25.0,
+			size:     0.35,
+			colors:   (color, Color::RGBA(color.r, color.g, color.b, 0)),
+			sprite:   Sprite::Tile(tile),
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Particle
+{
+	x:  f64,
+	y:  f64,
+	vx: f64,
+	vy: f64,
+
+	age:  f64,
+	life: f64,
+
+	gravity: f64,
+	size:    f64,
+	colors:  (Color, Color),
+	sprite:  Sprite,
+}
+
+/// Particles in field coordinates, moving in real time.
+#[derive(Debug)]
+pub struct Particles
+{
+	pub enabled: bool,
+	/// Factor applied to the count of every emitter.
+	pub amount:  f64,
+
+	particles: Vec<Particle>,
+	updated:   Instant,
+
+	rng: StdRng,
+}
+
+impl Default for Particles
+{
+	fn default() -> Self
+	{
+		Particles {
+			enabled:   true,
+			amount:    1.0,
+			particles: Vec::new(),
+			updated:   Instant::now(),
+			rng:       StdRng::from_entropy(),
+		}
+	}
+}
+
+impl Particles
+{
+	/// Emits particles from the given position on the field.
+	pub fn emit(&mut self, e: &Emitter, x: f64, y: f64)
+	{
+		if !self.enabled
+		{
+			return;
+		}
+
+		let count = (e.count as f64 * self.amount).round() as usize;
+		let count = count.min(MAX_PARTICLES.saturating_sub(self.particles.len()));
+
+		let rng = &mut self.rng;
+		let mut range = |(from, to): (f64, f64)| {
+			if from < to
+			{
+				rng.gen_range(from..to)
+			}
+			else
+			{
+				from
+			}
+		};
+
+		for _ in 0..count
+		{
+			let angle = range(e.angle);
+			let speed = range(e.speed);
+			let life = range(e.lifetime);
+
+			self.particles.push(Particle {
+				x,
+				y,
+				vx: angle.cos() * speed,
+				vy: angle.sin() * speed,
+				age: 0.0,
+				life,
+				gravity: e.gravity,
+				size: e.size,
+				colors: e.colors,
+				sprite: e.sprite,
+			});
+		}
+	}
+
+	/// Moves the particles by the time passed since the last update and removes the expired ones.
+	pub fn update(&mut self)
+	{
+		let dt = self.updated.elapsed().as_secs_f64();
+		self.updated = Instant::now();
+
+		for p in &mut self.particles
+		{
+			p.age += dt;
+			p.vy += p.gravity * dt;
+			p.x += p.vx * dt;
+			p.y += p.vy * dt;
+		}
+
+		self.particles.retain(|p| p.age < p.life);
+	}
+}
+
+/// Draws the particles over the field.
+pub fn draw_particles<T: RenderTarget>(
+	rend: &mut Renderer, canvas: &mut Canvas<T>, field_rect: Rect, bs: u32,
+)
+{
+	let bs = bs as f64;
+
+	for i in 0..rend.particles.particles.len()
+	{
+		let p = &rend.particles.particles[i];
+
+		let c = lerp(p.colors.0, p.colors.1, p.age / p.life);
+		let size = (p.size * bs).round().max(1.0) as u32;
+		let center = Point::new(
+			field_rect.x + (p.x * bs).round() as i32,
+			field_rect.y + (p.y * bs).round() as i32,
+		);
+		let r = Rect::from_center(center, size, size);
+
+		match p.sprite
+		{
+			Sprite::Square =>
+			{
+				canvas.set_draw_color(c);
+				canvas.fill_rect(r).unwrap();
+			},
+			Sprite::Tile(t) =>
+			{
+				let src = rend.tile_rect(t);
+				let btex = &mut rend.block_texture;
+
+				btex.set_color_mod(c.r, c.g, c.b);
+				btex.set_alpha_mod(c.a);
+				canvas.copy(btex, src, r).unwrap();
+				btex.set_alpha_mod(255);
+			},
+		}
+	}
+}
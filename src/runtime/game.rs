@@ -1,59 +1,127 @@
-use std::{path::Path, time::Instant};
+use std::{
+	path::PathBuf,
+	time::{Duration, Instant},
+};
 
 use log::*;
-use sdl2::rect::Point;
+use sdl2::{keyboard::Keycode, pixels::Color, rect::Point};
 
 use super::{
-	drawer, profile_api,
+	drawer::{self, capture, particles::Emitter},
+	profile_api,
 	profile_api::Profile,
 	state::{
 		self, field,
 		flags::{self, Flag},
-		pieces, Direction,
+		pieces, Direction, GameEvent, Rotation,
 	},
+	watcher::Watcher,
 	Framework, Piece,
 };
-use crate::error::Error;
+use crate::{
+	error::Error,
+	lua,
+	menu::profile,
+	settings::{GhostMode, Settings},
+};
 
-pub struct Game<'a, 'b, 'd, 'e, 'f, 'g>
+pub struct Game<'b, 'd, 'e, 'f, 'g>
 {
 	pub state: state::TetrisState,
 	pub rend:  drawer::Renderer<'e>,
-	pub fw:    Framework<'a, 'b, 'd, 'e, 'f, 'g>,
+	pub fw:    Framework<'b, 'd, 'e, 'f, 'g>,
+
+	/// Error shown in place of the game until dismissed.
+	pub error: Option<String>,
+
+	/// Actions declared by the profile.
+	pub actions: Vec<profile_api::Action>,
+
+	/// Profile scripts, parents first, and the watcher of every file they loaded.
+	pub scripts: Vec<PathBuf>,
+	pub watcher: Watcher,
+
+	pub settings: Settings,
+	/// Tilesheet of the skin, also used to export the field.
+	pub texture:  PathBuf,
+
+	/// Whether the next frame is saved as a screenshot.
+	pub screenshot: bool,
 }
 
-pub fn init_game<'a, 'b, 'c, 'd, 'e, 'f>(
-	fw: Framework<'a, 'b, 'c, 'd, 'e, 'f>, win_dim: (u32, u32), t: Profile,
-) -> Result<Game<'a, 'b, 'c, 'd, 'e, 'f>, Error>
+pub fn init_game<'b, 'c, 'd, 'e, 'f>(
+	fw: Framework<'b, 'c, 'd, 'e, 'f>, win_dim: (u32, u32), t: Profile, chain: &[profile::Profile],
+	settings: &Settings,
+) -> Result<Game<'b, 'c, 'd, 'e, 'f>, Error>
 {
-	let rend = drawer::init_renderer(
-		&fw.tex_maker,
-		win_dim,
-		Path::new("Profiles/default/template.bmp"),
-	)?;
+	let (skin, texture) = super::resolve_skin(chain, &t.skin)?;
+	let mut rend = drawer::init_renderer(&fw.tex_maker, win_dim, &texture, skin.ghost_tile)?;
+	rend.bg_color = t.bg_color;
+	rend.field_bg_color = t.field_bg_color;
+	rend.field_border_color = t.field_edge_color;
+	rend.grid_color = t.grid_color;
+	rend.hud = t.hud;
+	rend.title = chain.first().map(|p| p.name.clone()).unwrap_or_default();
+	rend.animations.durations = t.animations;
+	rend.particles.enabled = settings.particles;
+	rend.particles.amount = settings.particle_amount;
+	rend.integer_scaling = settings.window.integer_scaling;
+	rend.ghost = if t.ghost
+	{
+		settings.ghost
+	}
+	else
+	{
+		GhostMode::Off
+	};
 
-	let state = state::init_game(
+	let mut state = state::init_game(
 		t.field_dim,
 		t.start_piece,
+		t.randomizer,
 		t.piece_hold_enabled,
 		t.piece_tick,
 	)?;
 
-	let mut game = Game { state, rend, fw };
+	state.garbage_tile = skin.garbage_tile.unwrap_or(0);
+	state.delays = t.delays;
+
+	let scripts = super::chain_scripts(chain);
+	let watcher = watch_scripts(&fw, &scripts)?;
+
+	let mut game = Game {
+		state,
+		rend,
+		fw,
+		error: None,
+		actions: t.actions,
+		scripts,
+		watcher,
+		settings: settings.clone(),
+		texture,
+		screenshot: false,
+	};
 	game.refresh_piece_view(t.piece_view_size)?;
 
 	Ok(game)
 }
 
-impl Game<'_, '_, '_, '_, '_, '_>
+fn watch_scripts(fw: &Framework, scripts: &[PathBuf]) -> Result<Watcher, Error>
+{
+	let mut files = lua::loaded_files(fw.lua)?;
+	files.extend_from_slice(scripts);
+
+	Ok(Watcher::new(files))
+}
+
+impl Game<'_, '_, '_, '_, '_>
 {
 	pub fn request_piece(&mut self) -> Result<Piece, Error>
 	{
 		let state = &mut self.state;
 		let fw = &self.fw;
 
-		let t = profile_api::call_lua("spawn_piece", state, fw)?;
-		profile_api::parse_pattern(t)
+		profile_api::call_spawn(state, fw)
 	}
 
 	pub fn refresh_piece_view(&mut self, size: usize) -> Result<(), Error>
@@ -75,8 +143,17 @@ impl Game<'_, '_, '_, '_, '_, '_>
 	{
 		info!("Respawning piece.");
 
-		let p = self.request_piece()?;
-		Ok(self.state.spawn_piece(p))
+		match self.request_piece()
+		{
+			Ok(p) => Ok(self.state.spawn_piece(p)),
+			Err(e) =>
+			{
+				// Respawn the current piece so the game stays playable after the error is dismissed
+				let p = self.state.player_piece.clone();
+				self.state.spawn_piece_direct(p);
+				Err(e)
+			},
+		}
 	}
 
 	pub fn place_piece<'a>(&mut self) -> Result<bool, Error>
@@ -85,23 +162,186 @@ impl Game<'_, '_, '_, '_, '_, '_>
 
 		let state = &mut self.state;
 		let fw = &self.fw;
+		let lines_before = state.lines_cleared;
 
 		// Add blocks to state
 		{
 			let fb = &mut state.field_blocks;
 			let fc = &mut state.field_colors;
+			let ft = &mut state.field_tiles;
 			let p = &state.player_piece;
 			let pp = state.player_pos;
 
 			fb.extend(p.blocks.iter().map(|b| Point::new(b.x + pp.x, b.y + pp.y)));
-			fc.extend(state.player_piece.colors.iter());
+			fc.extend(p.colors.iter());
+			ft.extend(p.tiles.iter());
 		}
 
-		profile_api::call_lua::<()>("on_place", state, fw)?;
+		let placed = profile_api::call_lua::<()>("on_place", state, fw);
 
 		state.pieces_placed += 1;
+		state.record_sample();
+
+		// Animate the lock and the lines cleared by the hook
+		{
+			let p = &state.player_piece;
+			let pp = state.player_pos;
+			let blocks = p.blocks.iter().map(|b| Point::new(b.x + pp.x, b.y + pp.y)).collect();
 
-		self.spawn_piece()
+			self.rend.animations.lock_flash(blocks);
+		}
+		self.play_events();
+
+		let state = &mut self.state;
+
+		let cleared = state.lines_cleared > lines_before;
+		let delay = state.delays.are
+			+ if cleared
+			{
+				state.delays.line_clear
+			}
+			else
+			{
+				Duration::default()
+			};
+
+		if delay > Duration::default()
+		{
+			// The next piece spawns once the delay passed, see `tick_update`
+			state.spawn_time = Some(Instant::now() + delay);
+			placed?;
+			return Ok(true);
+		}
+
+		// The next piece is spawned even if the hook failed, leaving the game in a playable state. A
+		// game over still ends the game when the hook error is reported.
+		let spawned = self.spawn_piece();
+		if let Ok(false) = spawned
+		{
+			self.state.exit = true;
+		}
+
+		placed?;
+		spawned
+	}
+
+	/// Plays the animations and particles of the events since the last call.
+	pub fn play_events(&mut self)
+	{
+		let state = &mut self.state;
+		let rend = &mut self.rend;
+
+		for e in state.events.drain(..)
+		{
+			match e
+			{
+				GameEvent::LineClear(c) =>
+				{
+					for ((b, col), t) in c.blocks.iter().zip(&c.colors).zip(&c.tiles)
+					{
+						let e = Emitter::shatter(*col, *t);
+						rend.particles.emit(&e, b.x as f64 + 0.5, b.y as f64 + 0.5);
+					}
+
+					rend.animations.line_clear(c, state.field_blocks.len());
+				},
+
+				GameEvent::PerfectClear =>
+				{
+					let (w, h) = state.field_size;
+					let e = Emitter::burst(Color::RGB(255, 215, 0), 120);
+					rend.particles.emit(&e, w as f64 / 2.0, h as f64 / 2.0);
+				},
+
+				GameEvent::Custom { name, x, y } =>
+				{
+					let color = match name.as_str()
+					{
+						"tspin" => Color::RGB(190, 80, 255),
+						_ => Color::WHITE,
+					};
+					rend.particles.emit(&Emitter::burst(color, 40), x, y);
+				},
+			}
+		}
+	}
+
+	/// Saves the frame drawn last, before it is presented.
+	pub fn save_frame(&mut self) -> Result<(), Error>
+	{
+		self.screenshot = false;
+
+		let format = self.settings.screenshot_format;
+		let file = capture::screenshot_file("screenshot", format)?;
+
+		capture::save_frame(self.fw.canvas, &file, format)
+	}
+
+	/// Saves an image of the field, without the player piece.
+	pub fn export_field(&self) -> Result<(), Error>
+	{
+		let state = &self.state;
+		let format = self.settings.screenshot_format;
+		let file = capture::screenshot_file("field", format)?;
+
+		let field = capture::FieldImage {
+			field_dim:  state.field_size,
+			blocks:     &state.field_blocks,
+			colors:     &state.field_colors,
+			tiles:      &state.field_tiles,
+			background: Color::BLACK,
+		};
+
+		capture::export_field(
+			&field,
+			&self.texture,
+			self.settings.export_block_size,
+			&file,
+			format,
+		)
+	}
+
+	/// Pauses the game and shows the error on screen.
+	pub fn report(&mut self, e: Error)
+	{
+		self.error = Some(e.message().to_owned());
+	}
+
+	/// Executes the profile scripts again in the running Lua context. The game state is kept, only
+	/// the script's functions and variables are replaced.
+	pub fn reload(&mut self) -> Result<(), Error>
+	{
+		info!("Reloading profile scripts.");
+
+		lua::reload_files(self.fw.lua, &self.scripts)?;
+		self.watcher = watch_scripts(&self.fw, &self.scripts)?;
+
+		if self.error.is_some()
+		{
+			self.resume();
+		}
+
+		Ok(())
+	}
+
+	/// Reloads the profile if one of its files changed on disk.
+	pub fn hot_reload(&mut self) -> Result<(), Error>
+	{
+		if self.watcher.changed()
+		{
+			self.reload()?;
+		}
+
+		Ok(())
+	}
+
+	/// Dismisses the shown error and continues the game.
+	pub fn resume(&mut self)
+	{
+		let state = &mut self.state;
+
+		self.error = None;
+		state.player_tick_time = Instant::now() + state.player_tick_dur;
 	}
 
 	pub fn swap(&mut self) -> Result<(), Error>
@@ -151,19 +391,68 @@ impl Game<'_, '_, '_, '_, '_, '_>
 		Ok(())
 	}
 
+	/// Name of the profile action bound to `key`.
+	pub fn action(&self, key: Keycode) -> Option<&str>
+	{
+		self.actions.iter().find(|a| a.key == key).map(|a| a.name.as_str())
+	}
+
+	pub fn run_action(&mut self, key: Keycode) -> Result<(), Error>
+	{
+		if let Some(name) = self.action(key).map(str::to_owned)
+		{
+			profile_api::call_action(&mut self.state, &self.fw, &name)?;
+		}
+
+		Ok(())
+	}
+
+	/// Rotates the player piece, following the profile's "rotate" hook if it has one.
+	pub fn rotate(&mut self, dir: Rotation) -> Result<bool, Error>
+	{
+		match profile_api::call_rotate(&mut self.state, &self.fw, dir)?
+		{
+			Some(rs) => Ok(rs.into_iter().any(|r| self.state.apply_rotation(r))),
+			None => Ok(self.state.rotate(dir)),
+		}
+	}
+
 	pub fn drop(&mut self) -> Result<bool, Error>
 	{
 		info!("Dropping piece.");
 
 		let state = &mut self.state;
+		let distance = state.player_proj - state.player_pos.y;
 		state.player_pos.y = state.player_proj;
 
+		let p = &state.player_piece;
+		let pp = state.player_pos;
+		let blocks = p.blocks.iter().map(|b| Point::new(b.x + pp.x, b.y + pp.y)).collect();
+		self.rend.animations.drop_trail(blocks, p.colors.clone(), distance);
+
 		self.place_piece()
 	}
 
 	pub fn tick_update(&mut self) -> Result<(), Error>
 	{
 		let state = &mut self.state;
+		let now = Instant::now();
+
+		if let Some(t) = state.spawn_time
+		{
+			if t <= now
+			{
+				state.spawn_time = None;
+				state.player_tick_time = now + state.player_tick_dur;
+
+				if !self.spawn_piece()?
+				{
+					self.state.exit = true;
+				}
+			}
+
+			return Ok(());
+		}
 
 		if state.player_tick_time <= Instant::now()
 		{
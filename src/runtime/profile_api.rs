@@ -1,12 +1,33 @@
-use std::{convert::TryFrom, ffi::c_void, time::Duration};
+use std::{convert::TryFrom, ffi::c_void, path::PathBuf, time::Duration};
 
 use log::info;
 use rlua::prelude::*;
 use sdl2::{pixels::Color, rect::Point};
 
-use super::{Framework, Piece, TetrisState};
+use super::{
+	drawer::{animation::Durations, overlay::Layer, Fill},
+	state::{
+		pieces::{self, Rotated},
+		randomizer::Randomizer,
+		Delays, GameEvent, Rotation,
+	},
+	Framework, Piece, Shape, Skin, TetrisState,
+};
 use crate::{error::Error, lua::*};
 
+mod actions;
+mod draw;
+mod piece_sets;
+mod randomizers;
+
+pub use self::{
+	actions::{call_action, Action},
+	randomizers::call_spawn,
+};
+
+/// Registry list of the `init_game` functions of a profile and its parents.
+const INIT_KEY: &str = "dedtris.init_game";
+
 // -----------------------------------------------------------------------------
 // Lua initialization
 // -----------------------------------------------------------------------------
@@ -16,17 +37,119 @@ pub struct Profile
 {
 	pub bg_color: Color,
 
-	pub field_bg_color:   Color,
+	pub field_bg_color:   Fill,
 	pub field_edge_color: Color,
+	pub grid_color:       Option<Color>,
 
 	pub field_dim: (u32, u32),
 
 	pub start_piece: Piece,
+	pub randomizer:  Option<Randomizer>,
 
 	pub piece_view_size:    usize,
 	pub piece_hold_enabled: bool,
 
 	pub piece_tick: Duration,
+	pub delays:     Delays,
+
+	/// Durations of the line clear, lock and hard drop animations.
+	pub animations: Durations,
+
+	/// Whether the stats are shown next to the field.
+	pub hud:   bool,
+	/// Whether the ghost piece is shown, profiles hiding it regardless of the user's settings.
+	pub ghost: bool,
+
+	pub actions: Vec<Action>,
+
+	pub skin: Skin,
+}
+
+// -----------------------------------------------------------------------------
+// Profile Chain
+// -----------------------------------------------------------------------------
+
+/// Executes the scripts of a profile and the profiles it extends, parents first, keeping the
+/// `init_game` function of every script.
+pub fn exec_scripts(ctx: &rlua::Context, scripts: &[PathBuf]) -> Result<(), Error>
+{
+	let g = ctx.globals();
+	let inits = ctx.create_table()?;
+	let mut count = 0;
+
+	for s in scripts
+	{
+		let parent = g.get::<_, LuaValue>("init_game")?;
+		g.set("init_game", LuaNil)?;
+
+		exec_file(ctx, s)?;
+
+		match g.get::<_, Option<LuaFunction>>("init_game")?
+		{
+			Some(f) =>
+			{
+				count += 1;
+				inits.set(count, f)?;
+			},
+			None => g.set("init_game", parent)?,
+		}
+	}
+
+	ctx.set_named_registry_value(INIT_KEY, inits)?;
+
+	Ok(())
+}
+
+/// Merges the `init_game` table of a child profile into the one of its parent. Tables present in
+/// both are merged as well, except lists and pieces which the child replaces.
+fn merge_init<'a>(parent: &LuaTable<'a>, child: LuaTable<'a>) -> LuaResult<()>
+{
+	let mergeable = |t: &LuaTable| -> LuaResult<bool> {
+		Ok(t.raw_len() == 0 && !t.contains_key("template")? && !t.contains_key("rotations")?)
+	};
+
+	for pair in child.pairs::<LuaValue, LuaValue>()
+	{
+		let (k, v) = pair?;
+
+		match (parent.get::<_, LuaValue>(k.clone())?, v)
+		{
+			(LuaValue::Table(p), LuaValue::Table(c)) if mergeable(&p)? && mergeable(&c)? =>
+			{
+				merge_init(&p, c)?
+			},
+			(_, v) => parent.set(k, v)?,
+		}
+	}
+
+	Ok(())
+}
+
+/// Calls the `init_game` functions collected by `exec_scripts` and merges their tables.
+fn call_init<'a>(ctx: &rlua::Context<'a>) -> Result<LuaTable<'a>, Error>
+{
+	let inits: LuaTable = ctx.named_registry_value(INIT_KEY)?;
+
+	let mut merged: Option<LuaTable> = None;
+	for f in inits.sequence_values::<LuaFunction>()
+	{
+		let t = f?.call::<_, LuaTable>(())?;
+
+		match &merged
+		{
+			Some(m) => merge_init(m, t)?,
+			None => merged = Some(t),
+		}
+	}
+
+	match merged
+	{
+		Some(t) => Ok(t),
+		None =>
+		{
+			find_function(&ctx.globals(), "init_game")?.call::<_, LuaTable>(()).map_err(Error::from)
+		},
+	}
 }
 
 // -----------------------------------------------------------------------------
@@ -37,9 +160,7 @@ pub fn load<'a, 'b>(ctx: &'b rlua::Context<'a>) -> Result<Profile, Error>
 {
 	info!("Evaluating profile.");
 
-	let g = ctx.globals();
-
-	let init = find_function(&g, "init_game")?.call::<_, rlua::Table>(())?;
+	let init = call_init(ctx)?;
 
 	let width = u32::try_from(find_int(&init, "width")?)?;
 	let height = u32::try_from(find_int(&init, "height")?)?;
@@ -53,7 +174,20 @@ pub fn load<'a, 'b>(ctx: &'b rlua::Context<'a>) -> Result<Profile, Error>
 		Duration::from_secs(3_155_760_000) // 100 Years
 	};
 
-	let start_piece = parse_pattern(find_table(&init, "start_piece")?)?;
+	let (delays, animations) = parse_timing(&init)?;
+
+	let mut randomizer = randomizers::parse_randomizer(ctx, &init)?;
+
+	// Without a start piece the game starts with the randomizer's first piece
+	let start_piece = match (
+		init.get::<_, Option<LuaTable>>("start_piece")?,
+		&mut randomizer,
+	)
+	{
+		(Some(t), _) => parse_pattern(t)?,
+		(None, Some(r)) => parse_pattern(randomizers::next_piece_table(*ctx, r)?)?,
+		(None, None) => parse_pattern(find_table(&init, "start_piece")?)?,
+	};
 
 	let piece_view_size = if let Ok(s) =
 		init.get::<_, LuaTable>("piece_view").and_then(|t| t.get::<_, LuaInteger>("size"))
@@ -70,63 +204,340 @@ pub fn load<'a, 'b>(ctx: &'b rlua::Context<'a>) -> Result<Profile, Error>
 		.and_then(|t| t.get::<_, bool>("enabled"))
 		.unwrap_or(false);
 
+	let hud = init.get::<_, Option<bool>>("hud")?.unwrap_or(true);
+	let ghost = init.get::<_, Option<bool>>("ghost")?.unwrap_or(true);
+
+	let actions = actions::parse_actions(ctx, &init)?;
+	let skin = parse_skin(&init)?;
+
+	// Colors of the game, all optional
+	let colors = init.get::<_, Option<LuaTable>>("colors")?.unwrap_or(ctx.create_table()?);
+	let color = |name| -> Result<Option<Color>, Error> {
+		colors.get::<_, Option<LuaTable>>(name)?.map(parse_piece_color).transpose()
+	};
+
+	let bg_color = color("background")?.unwrap_or(Color::GRAY);
+	let field_edge_color = color("border")?.unwrap_or(Color::GRAY);
+	let grid_color = color("grid")?;
+
+	let field_bg_color = match colors.get::<_, Option<LuaTable>>("field")?
+	{
+		Some(t) => parse_fill(t)?,
+		None => Fill::Solid(Color::BLACK),
+	};
+
 	Ok(Profile {
-		bg_color: Color::WHITE,
-		field_bg_color: Color::BLACK,
-		field_edge_color: Color::GRAY,
+		bg_color,
+		field_bg_color,
+		field_edge_color,
+		grid_color,
 		field_dim: (width, height),
 		piece_tick,
+		delays,
+		animations,
 		start_piece,
+		randomizer,
 		piece_view_size,
 		piece_hold_enabled,
+		hud,
+		ghost,
+		actions,
+		skin,
 	})
 }
 
+/// Parses the optional "delays" and "animations" tables of `init_game`, durations being given in
+/// milliseconds:
+///
+/// ```lua
+/// delays = { are = 100, line_clear = 300 },
+/// animations = { line_clear = 300, lock_flash = 120, drop_trail = 150 },
+/// ```
+///
+/// Delays default to zero, `animations = false` turns every animation off.
+fn parse_timing(init: &LuaTable) -> Result<(Delays, Durations), Error>
+{
+	let millis = |t: &LuaTable, key: &str, default: Duration| -> Result<Duration, Error> {
+		match t.get::<_, Option<LuaInteger>>(key)?
+		{
+			Some(ms) => Ok(Duration::from_millis(u64::try_from(ms)?)),
+			None => Ok(default),
+		}
+	};
+
+	let delays = match init.get::<_, Option<LuaTable>>("delays")?
+	{
+		Some(t) => Delays {
+			are:        millis(&t, "are", Duration::default())?,
+			line_clear: millis(&t, "line_clear", Duration::default())?,
+		},
+		None => Delays::default(),
+	};
+
+	let default = Durations::default();
+	let animations = match init.get::<_, LuaValue>("animations")?
+	{
+		LuaValue::Nil | LuaValue::Boolean(true) => default,
+		LuaValue::Boolean(false) => Durations::NONE,
+		LuaValue::Table(t) => Durations {
+			line_clear: millis(&t, "line_clear", default.line_clear)?,
+			lock_flash: millis(&t, "lock_flash", default.lock_flash)?,
+			drop_trail: millis(&t, "drop_trail", default.drop_trail)?,
+		},
+		_ => return Err(Error::from("\"animations\" must be a table or a boolean.")),
+	};
+
+	Ok((delays, animations))
+}
+
+/// Parses a color or a gradient given as `{ from = color, to = color, direction = "rows" }`, the
+/// direction being "rows" or "columns".
+fn parse_fill(t: LuaTable) -> Result<Fill, Error>
+{
+	if !t.contains_key("from")?
+	{
+		return Ok(Fill::Solid(parse_piece_color(t)?));
+	}
+
+	let from = parse_piece_color(find_table(&t, "from")?)?;
+	let to = parse_piece_color(find_table(&t, "to")?)?;
+
+	match t.get::<_, Option<String>>("direction")?.as_deref()
+	{
+		None | Some("rows") => Ok(Fill::Rows(from, to)),
+		Some("columns") => Ok(Fill::Columns(from, to)),
+		Some(d) => Err(Error::from(format!(
+			"Unknown gradient direction \"{}\", expected \"rows\" or \"columns\".",
+			d
+		))),
+	}
+}
+
+/// Parses the optional "skin" table of `init_game`:
+///
+/// ```lua
+/// skin = { texture = "gems.png", ghost_tile = 7, garbage_tile = 8 },
+/// ```
+fn parse_skin(init: &LuaTable) -> Result<Skin, Error>
+{
+	let t = match init.get::<_, Option<LuaTable>>("skin")?
+	{
+		Some(t) => t,
+		None => return Ok(Skin::default()),
+	};
+
+	Ok(Skin {
+		texture:      t.get("texture")?,
+		ghost_tile:   t.get("ghost_tile")?,
+		garbage_tile: t.get("garbage_tile")?,
+	})
+}
+
+/// Checks an evaluated profile for problems that would only show once the game runs. Returns
+/// warnings about hooks that are missing but not required.
+pub fn check(ctx: &rlua::Context, t: &Profile) -> Result<Vec<String>, Error>
+{
+	let g = ctx.globals();
+	let defined =
+		|name| -> Result<bool, Error> { Ok(g.get::<_, Option<LuaFunction>>(name)?.is_some()) };
+
+	let (w, h) = t.field_dim;
+	if w == 0 || h == 0
+	{
+		return Err(Error::from(
+			"The field must be at least one block wide and high.",
+		));
+	}
+
+	if pieces::spawn_piece(&[], t.field_dim, t.start_piece.clone()).is_none()
+	{
+		return Err(Error::from("The start piece doesn't fit into the field."));
+	}
+
+	if t.randomizer.is_none() && !defined("spawn_piece")?
+	{
+		return Err(Error::from(
+			"The profile needs either a \"spawn_piece\" function or a randomizer.",
+		));
+	}
+
+	if !defined("on_place")?
+	{
+		return Err(Error::from(
+			"The profile needs an \"on_place\" function, it is called for every placed piece.",
+		));
+	}
+
+	let mut warnings = Vec::new();
+
+	if t.randomizer.is_some() && defined("spawn_piece")?
+	{
+		warnings.push(
+			"\"spawn_piece\" is defined, the randomizer is only used through \"_nextPiece\"."
+				.to_owned(),
+		);
+	}
+
+	Ok(warnings)
+}
+
 // -----------------------------------------------------------------------------
 // Parsing Functions
 // -----------------------------------------------------------------------------
 
+/// Parses a piece table. Template cells are `0` for empty and `1` for a block of the piece's
+/// `color`; other digits and letters name an entry of the optional `colors` palette:
+///
+/// ```lua
+/// { size = 2, template = "1a a2", color = {...}, colors = { a = {...}, [2] = {..., tile = 1} } }
+/// ```
+///
+/// Palette entries and the piece itself may select a `tile` of the block texture.
 pub fn parse_pattern(table: LuaTable) -> Result<Piece, Error>
 {
 	let dim = u32::try_from(find_int(&table, "size")?)?;
 
-	let blocks = parse_piece_body(find_string(&table, "template")?, dim)?;
+	let rotations = parse_piece_rotations(&table, dim)?;
 
-	let color = parse_piece_color(find_table(&table, "color")?)?;
-	let colors = vec![color; blocks.len()];
+	let first = match rotations.first()
+	{
+		Some(r) if table.get::<_, Option<LuaString>>("template")?.is_none() => r.clone(),
+		_ => parse_piece_shape(&table, find_string(&table, "template")?, dim)?,
+	};
+
+	if rotations.iter().any(|r| r.blocks.len() != first.blocks.len())
+	{
+		return Err(Error::from(
+			"Every rotation of a piece must have the same number of blocks.",
+		));
+	}
+
+	let Shape {
+		blocks,
+		colors,
+		tiles,
+	} = first;
 
 	Ok(Piece {
 		dim,
 		blocks,
 		colors,
+		tiles,
+		rotations,
+		rotation: 0,
 	})
 }
 
-fn parse_piece_body(data: LuaString, pd: u32) -> Result<Vec<Point>, Error>
+/// Parses the optional list of templates describing every orientation of a piece, starting with
+/// the spawn orientation and continuing clockwise.
+fn parse_piece_rotations(table: &LuaTable, pd: u32) -> Result<Vec<Shape>, Error>
+{
+	let rotations = match table.get::<_, Option<LuaTable>>("rotations")?
+	{
+		Some(r) => r,
+		None => return Ok(Vec::new()),
+	};
+
+	let mut v = Vec::new();
+	for r in rotations.sequence_values::<LuaString>()
+	{
+		v.push(parse_piece_shape(table, r?, pd)?);
+	}
+
+	Ok(v)
+}
+
+/// Parses a template and resolves the color and tile of every block.
+fn parse_piece_shape(table: &LuaTable, data: LuaString, pd: u32) -> Result<Shape, Error>
+{
+	let cells = parse_piece_body(data, pd)?;
+
+	let palette = table.get::<_, Option<LuaTable>>("colors")?;
+	let color = table.get::<_, Option<LuaTable>>("color")?;
+	let tile = table.get::<_, Option<u32>>("tile")?.unwrap_or(0);
+
+	let mut shape = Shape {
+		blocks: Vec::with_capacity(cells.len()),
+		colors: Vec::with_capacity(cells.len()),
+		tiles:  Vec::with_capacity(cells.len()),
+	};
+
+	for (p, key) in cells
+	{
+		let entry = match &palette
+		{
+			Some(t) if key.is_ascii_digit() => t.get::<_, Option<LuaTable>>(key - b'0')?,
+			Some(t) => t.get::<_, Option<LuaTable>>((key as char).to_string())?,
+			None => None,
+		};
+
+		let (c, t) = match entry
+		{
+			Some(e) =>
+			{
+				let t = e.get::<_, Option<u32>>("tile")?.unwrap_or(tile);
+				(parse_piece_color(e)?, t)
+			},
+			None if key == b'1' =>
+			{
+				let c = color.clone().ok_or_else(|| Error::from("A piece needs a \"color\"."))?;
+				(parse_piece_color(c)?, tile)
+			},
+			None =>
+			{
+				return Err(Error::from(format!(
+					"The piece palette has no color \"{}\".",
+					key as char
+				)))
+			},
+		};
+
+		shape.blocks.push(p);
+		shape.colors.push(c);
+		shape.tiles.push(t);
+	}
+
+	Ok(shape)
+}
+
+/// Inverse of `parse_piece_body`.
+fn piece_template(blocks: &[Point], pd: u32) -> String
+{
+	let mut s = vec![b'0'; (pd * pd) as usize];
+
+	for b in blocks
+	{
+		s[(b.y as u32 * pd + b.x as u32) as usize] = b'1';
+	}
+
+	String::from_utf8(s).unwrap()
+}
+
+/// Returns the filled cells of a template together with the character filling them.
+fn parse_piece_body(data: LuaString, pd: u32) -> Result<Vec<(Point, u8)>, Error>
 {
 	let ps = pd * pd;
 
 	let mut field = Vec::with_capacity(ps as usize);
 	let mut blocks = 0;
 
-	for (i, c) in data.as_bytes().iter().enumerate()
+	for c in data.as_bytes()
 	{
 		match c
 		{
-			b'1' =>
+			b'0' => blocks += 1,
+			b'1'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' =>
 			{
-				field.push(Point::new(
-					(i % pd as usize) as i32,
-					(i / pd as usize) as i32,
-				));
+				field.push((Point::new((blocks % pd) as i32, (blocks / pd) as i32), *c));
 				blocks += 1;
 			},
-			b'0' => blocks += 1,
 			b'\n' | b' ' | b'\r' | b'\t' =>
 			{},
 			_ =>
 			{
-				return Err(Error::from("Characters must be 0's or 1's."));
+				return Err(Error::from(
+					"Characters must be 0's, 1's or keys of the piece palette.",
+				));
 			},
 		}
 	}
@@ -135,8 +546,7 @@ fn parse_piece_body(data: LuaString, pd: u32) -> Result<Vec<Point>, Error>
 	{
 		return Err(Error::from(format!(
 			"A piece size doesn't match it's given size. is: {}, should be: {}.",
-			field.len(),
-			ps
+			blocks, ps
 		)));
 	}
 
@@ -155,18 +565,30 @@ fn parse_piece_color(data: LuaTable) -> Result<Color, Error>
 	Ok(Color::RGBA(rgba[0], rgba[1], rgba[2], rgba[3]))
 }
 
+/// Inverse of `parse_piece_color`.
+fn color_table<'a>(ctx: rlua::Context<'a>, c: Color) -> LuaResult<LuaTable<'a>>
+{
+	let t = ctx.create_table()?;
+	t.set("r", c.r)?;
+	t.set("g", c.g)?;
+	t.set("b", c.b)?;
+	t.set("a", c.a)?;
+	Ok(t)
+}
+
 // -----------------------------------------------------------------------------
 // Lua Functions
 // -----------------------------------------------------------------------------
 
-pub struct StateData<'a, 'b, 'c, 'd, 'f, 'g, 'h, 'i>
+pub struct StateData<'a, 'b, 'd, 'f, 'g, 'h, 'i>
 {
 	pub game: &'a mut TetrisState,
-	pub fw:   &'b Framework<'c, 'd, 'f, 'g, 'h, 'i>,
+	pub fw:   &'b Framework<'d, 'f, 'g, 'h, 'i>,
+	pub draw: Option<&'a mut Layer>,
 }
 
 pub fn call_lua<'a, T>(
-	name: &str, state: &mut TetrisState, fw: &Framework<'_, '_, '_, '_, '_, 'a>,
+	name: &str, state: &mut TetrisState, fw: &Framework<'_, '_, '_, '_, 'a>,
 ) -> Result<T, Error>
 where
 	T: rlua::FromLuaMulti<'a>,
@@ -176,13 +598,138 @@ where
 	let ctx = &fw.lua;
 	let g = ctx.globals();
 
-	let mut data = StateData { game: state, fw };
+	let mut data = StateData {
+		game: state,
+		fw,
+		draw: None,
+	};
 
 	let ptr = &mut data as *mut _ as *mut c_void;
 	Ok(find_function(&g, name)?.call::<_, T>(rlua::LightUserData { 0: ptr })?)
 }
 
-pub fn load_defaults(ctx: &rlua::Context) -> Result<(), Error>
+/// Calls the optional "on_draw" hook, collecting the issued draw commands into `layer`.
+pub fn call_draw(
+	state: &mut TetrisState, fw: &Framework<'_, '_, '_, '_, '_>, layer: &mut Layer,
+) -> Result<(), Error>
+{
+	let ctx = &fw.lua;
+	let g = ctx.globals();
+
+	let f = match g.get::<_, Option<LuaFunction>>("on_draw")?
+	{
+		Some(f) => f,
+		None => return Ok(()),
+	};
+
+	layer.clear();
+
+	let mut data = StateData {
+		game: state,
+		fw,
+		draw: Some(layer),
+	};
+
+	let ptr = &mut data as *mut _ as *mut c_void;
+	f.call::<_, ()>(rlua::LightUserData(ptr))?;
+
+	Ok(())
+}
+
+/// Asks the optional "rotate" hook for the orientations to try, in order, when rotating the
+/// player piece. Returns `None` if the hook isn't defined.
+pub fn call_rotate(
+	state: &mut TetrisState, fw: &Framework<'_, '_, '_, '_, '_>, dir: Rotation,
+) -> Result<Option<Vec<Rotated>>, Error>
+{
+	let ctx = &fw.lua;
+	let g = ctx.globals();
+
+	let f = match g.get::<_, Option<LuaFunction>>("rotate")?
+	{
+		Some(f) => f,
+		None => return Ok(None),
+	};
+
+	info!("Querying \"rotate\".");
+
+	let p = &state.player_piece;
+	let (dim, rotation, count) = (p.dim, p.rotation, p.blocks.len());
+
+	let piece = ctx.create_table()?;
+	piece.set("size", dim)?;
+	piece.set("template", piece_template(&p.blocks, dim))?;
+	piece.set("rotation", rotation + 1)?;
+	piece.set("x", state.player_pos.x)?;
+	piece.set("y", state.player_pos.y)?;
+
+	let dir = match dir
+	{
+		Rotation::Clockwise => 1,
+		Rotation::CounterClockwise => -1,
+	};
+
+	let mut data = StateData {
+		game: state,
+		fw,
+		draw: None,
+	};
+
+	let ptr = &mut data as *mut _ as *mut c_void;
+	let r = f.call::<_, LuaValue>((rlua::LightUserData(ptr), piece, dir))?;
+
+	// A single orientation or a list of them to try in order
+	let tables = match r
+	{
+		LuaValue::Table(t) if t.contains_key("template")? => vec![t],
+		LuaValue::Table(t) => t.sequence_values::<LuaTable>().collect::<LuaResult<_>>()?,
+		LuaValue::Nil | LuaValue::Boolean(false) => Vec::new(),
+		_ =>
+		{
+			return Err(Error::from(
+				"\"rotate\" must return a table, a list of tables or nil.",
+			))
+		},
+	};
+
+	let mut v = Vec::with_capacity(tables.len());
+	for t in tables
+	{
+		// Blocks keep their colors in template order
+		let blocks: Vec<Point> = parse_piece_body(find_string(&t, "template")?, dim)?
+			.into_iter()
+			.map(|(p, _)| p)
+			.collect();
+
+		if blocks.len() != count
+		{
+			return Err(Error::from(
+				"A rotation must keep the number of blocks of the piece.",
+			));
+		}
+
+		let x = t.get::<_, Option<i32>>("x")?.unwrap_or(0);
+		let y = t.get::<_, Option<i32>>("y")?.unwrap_or(0);
+
+		let rotation = match t.get::<_, Option<usize>>("rotation")?
+		{
+			Some(r) if r > 0 => r - 1,
+			Some(_) => return Err(Error::from("Rotations are counted from 1.")),
+			None => rotation,
+		};
+
+		v.push(Rotated {
+			blocks,
+			offset: Point::new(x, y),
+			rotation,
+			style: None,
+		});
+	}
+
+	Ok(Some(v))
+}
+
+pub fn load_defaults(ctx: &rlua::Context, paths: &SearchPaths) -> Result<(), Error>
 {
 	let solve_field = ctx.create_function(|_, data: rlua::LightUserData| {
 		let StateData { game, .. }: &mut StateData = unsafe { &mut *(data.0 as *mut StateData) };
@@ -200,9 +747,91 @@ pub fn load_defaults(ctx: &rlua::Context) -> Result<(), Error>
 		Ok(())
 	})?;
 
+	let event = ctx.create_function(
+		|_, (data, name, x, y): (rlua::LightUserData, String, Option<f64>, Option<f64>)| {
+			let StateData { game, .. }: &mut StateData =
+				unsafe { &mut *(data.0 as *mut StateData) };
+
+			// Without a position the event happens at the player piece
+			let p = &game.player_piece;
+			let center = (p.dim as f64 / 2.0, p.dim as f64 / 2.0);
+
+			game.events.push(GameEvent::Custom {
+				name,
+				x: x.unwrap_or(game.player_pos.x as f64 + center.0),
+				y: y.unwrap_or(game.player_pos.y as f64 + center.1),
+			});
+
+			Ok(())
+		},
+	)?;
+
+	let get_field = ctx.create_function(|ctx, data: rlua::LightUserData| {
+		let StateData { game, .. }: &mut StateData = unsafe { &mut *(data.0 as *mut StateData) };
+
+		let t = ctx.create_table()?;
+
+		let fb = &game.field_blocks;
+		let cells = game.field_colors.iter().zip(&game.field_tiles);
+
+		for (i, (b, (c, tile))) in fb.iter().zip(cells).enumerate()
+		{
+			let block = ctx.create_table()?;
+			block.set("x", b.x)?;
+			block.set("y", b.y)?;
+			block.set("color", color_table(ctx, *c)?)?;
+			block.set("tile", *tile)?;
+
+			t.set(i + 1, block)?;
+		}
+
+		Ok(t)
+	})?;
+
+	let set_field = ctx.create_function(|_, (data, blocks): (rlua::LightUserData, LuaTable)| {
+		let StateData { game, .. }: &mut StateData = unsafe { &mut *(data.0 as *mut StateData) };
+
+		let (w, h) = game.field_size;
+		let mut fb = Vec::new();
+		let mut fc = Vec::new();
+		let mut ft = Vec::new();
+
+		for block in blocks.sequence_values::<LuaTable>()
+		{
+			let block = block?;
+			let p = Point::new(block.get("x")?, block.get("y")?);
+
+			if p.x < 0 || p.y < 0 || p.x >= w as i32 || p.y >= h as i32 || fb.contains(&p)
+			{
+				return Err(LuaError::RuntimeError(format!(
+					"Block ({}, {}) is outside of the field or given twice.",
+					p.x, p.y
+				)));
+			}
+
+			let c = parse_piece_color(block.get("color")?)
+				.map_err(|_| LuaError::RuntimeError("Invalid block color.".to_owned()))?;
+
+			fb.push(p);
+			fc.push(c);
+			ft.push(block.get::<_, Option<u32>>("tile")?.unwrap_or(game.garbage_tile));
+		}
+
+		game.set_field(fb, fc, ft);
+
+		Ok(())
+	})?;
+
 	let g = ctx.globals();
 	g.set("_solveField", solve_field)?;
 	g.set("_finishGame", exit_game)?;
+	g.set("_event", event)?;
+	g.set("_getField", get_field)?;
+	g.set("_setField", set_field)?;
+
+	draw::load_defaults(ctx)?;
+	piece_sets::load_defaults(ctx, paths)?;
+	randomizers::load_defaults(ctx)?;
 
 	Ok(())
 }
@@ -0,0 +1,146 @@
+use std::ffi::c_void;
+
+use log::{info, warn};
+use rlua::prelude::*;
+use sdl2::keyboard::Keycode;
+
+use super::StateData;
+use crate::{
+	error::Error,
+	lua::find_string,
+	runtime::{Framework, TetrisState},
+};
+
+/// Registry table mapping action names to their handlers.
+const ACTIONS_KEY: &str = "dedtris.actions";
+
+/// Keys controlling the piece, actions using them take precedence.
+const BUILTIN_KEYS: [Keycode; 8] = [
+	Keycode::Left,
+	Keycode::Right,
+	Keycode::Down,
+	Keycode::Up,
+	Keycode::X,
+	Keycode::Z,
+	Keycode::LShift,
+	Keycode::Space,
+];
+
+/// Keys handled before actions are dispatched, which actions can't use.
+const RESERVED_KEYS: [Keycode; 4] = [Keycode::Escape, Keycode::F5, Keycode::F9, Keycode::F12];
+
+/// Action declared by a profile and the key triggering it.
+#[derive(Debug)]
+pub struct Action
+{
+	pub name: String,
+	pub key:  Keycode,
+}
+
+// -----------------------------------------------------------------------------
+// Action Parsing
+// -----------------------------------------------------------------------------
+
+/// Parses the optional "actions" table of `init_game`:
+///
+/// ```lua
+/// actions = {
+///     use_item = { key = "E", on_press = function(state, name) end },
+/// }
+/// ```
+///
+/// `on_press` can also name a global function, which is looked up when the action is triggered
+/// and so follows script reloads.
+pub fn parse_actions<'a>(ctx: &rlua::Context<'a>, init: &LuaTable<'a>)
+	-> Result<Vec<Action>, Error>
+{
+	let handlers = ctx.create_table()?;
+	let mut v = Vec::new();
+
+	if let Some(t) = init.get::<_, Option<LuaTable>>("actions")?
+	{
+		for pair in t.pairs::<String, LuaTable>()
+		{
+			let (name, action) = pair?;
+
+			let key_name = find_string(&action, "key")?;
+			let key_name = key_name.to_str()?;
+
+			let key = Keycode::from_name(key_name).ok_or_else(|| {
+				Error::from(format!(
+					"Unknown key \"{}\" for action \"{}\".",
+					key_name, name
+				))
+			})?;
+
+			if RESERVED_KEYS.contains(&key)
+			{
+				return Err(Error::from(format!(
+					"Action \"{}\" can't use \"{}\", which is reserved by the game.",
+					name, key_name
+				)));
+			}
+
+			if BUILTIN_KEYS.contains(&key)
+			{
+				warn!(
+					"Action \"{}\" replaces the built-in binding of \"{}\".",
+					name, key_name
+				);
+			}
+
+			match action.get::<_, LuaValue>("on_press")?
+			{
+				h @ LuaValue::Function(_) | h @ LuaValue::String(_) =>
+				{
+					handlers.set(name.as_str(), h)?
+				},
+				_ =>
+				{
+					return Err(Error::from(format!(
+						"Action \"{}\" needs a function or function name as \"on_press\".",
+						name
+					)))
+				},
+			}
+
+			v.push(Action { name, key });
+		}
+	}
+
+	ctx.set_named_registry_value(ACTIONS_KEY, handlers)?;
+
+	Ok(v)
+}
+
+// -----------------------------------------------------------------------------
+// Action Dispatch
+// -----------------------------------------------------------------------------
+
+pub fn call_action(
+	state: &mut TetrisState, fw: &Framework<'_, '_, '_, '_, '_>, name: &str,
+) -> Result<(), Error>
+{
+	info!("Triggering action \"{}\".", name);
+
+	let ctx = &fw.lua;
+	let handlers: LuaTable = ctx.named_registry_value(ACTIONS_KEY)?;
+
+	let f = match handlers.get::<_, LuaValue>(name)?
+	{
+		LuaValue::Function(f) => f,
+		LuaValue::String(s) => ctx.globals().get::<_, LuaFunction>(s)?,
+		_ => return Err(Error::from(format!("Action \"{}\" has no handler.", name))),
+	};
+
+	let mut data = StateData {
+		game: state,
+		fw,
+		draw: None,
+	};
+
+	let ptr = &mut data as *mut _ as *mut c_void;
+	f.call::<_, ()>((rlua::LightUserData(ptr), name))?;
+
+	Ok(())
+}
@@ -0,0 +1,163 @@
+use rlua::prelude::*;
+use sdl2::pixels::Color;
+
+use super::{parse_pattern, parse_piece_color, StateData};
+use crate::runtime::drawer::overlay::{Anchor, Command, Layer};
+
+// -----------------------------------------------------------------------------
+// Draw Command Parsing
+// -----------------------------------------------------------------------------
+
+fn parse_anchor(t: &LuaTable) -> LuaResult<Anchor>
+{
+	match t.get::<_, Option<String>>("relative")?.as_deref()
+	{
+		None | Some("field") => Ok(Anchor::Field),
+		Some("panel") => Ok(Anchor::Panel),
+		Some("window") => Ok(Anchor::Window),
+		Some(s) => Err(LuaError::RuntimeError(format!(
+			"Unknown coordinate space \"{}\", expected \"field\", \"panel\" or \"window\".",
+			s
+		))),
+	}
+}
+
+fn parse_number(t: &LuaTable, name: &str, default: f64) -> LuaResult<f64>
+{
+	Ok(t.get::<_, Option<f64>>(name)?.unwrap_or(default))
+}
+
+fn parse_color(t: &LuaTable, name: &str, default: Color) -> LuaResult<Color>
+{
+	match t.get::<_, Option<LuaTable>>(name)?
+	{
+		Some(c) => parse_piece_color(c)
+			.map_err(|_| LuaError::RuntimeError(format!("Invalid color \"{}\".", name))),
+		None => Ok(default),
+	}
+}
+
+/// Default size of a unit in the given coordinate space, one block or one 16 pixel line.
+fn default_size(anchor: Anchor) -> f64
+{
+	match anchor
+	{
+		Anchor::Field | Anchor::Panel => 1.0,
+		Anchor::Window => 16.0,
+	}
+}
+
+fn parse_text(t: &LuaTable) -> LuaResult<Command>
+{
+	let anchor = parse_anchor(t)?;
+
+	Ok(Command::Text {
+		anchor,
+		x: parse_number(t, "x", 0.0)?,
+		y: parse_number(t, "y", 0.0)?,
+		size: parse_number(t, "size", default_size(anchor))?,
+		text: t.get::<_, String>("text")?,
+		color: parse_color(t, "color", Color::WHITE)?,
+	})
+}
+
+fn parse_rect(t: &LuaTable) -> LuaResult<Command>
+{
+	Ok(Command::Rect {
+		anchor: parse_anchor(t)?,
+		x:      parse_number(t, "x", 0.0)?,
+		y:      parse_number(t, "y", 0.0)?,
+		w:      parse_number(t, "w", 0.0)?,
+		h:      parse_number(t, "h", 0.0)?,
+		color:  parse_color(t, "color", Color::WHITE)?,
+	})
+}
+
+fn parse_piece(t: &LuaTable) -> LuaResult<Command>
+{
+	let anchor = parse_anchor(t)?;
+	let piece = parse_pattern(t.get::<_, LuaTable>("piece")?)
+		.map_err(|_| LuaError::RuntimeError("Invalid piece passed to _drawPiece.".to_owned()))?;
+
+	Ok(Command::Piece {
+		anchor,
+		x: parse_number(t, "x", 0.0)?,
+		y: parse_number(t, "y", 0.0)?,
+		size: parse_number(t, "size", default_size(anchor))?,
+		piece,
+	})
+}
+
+fn parse_progress(t: &LuaTable) -> LuaResult<Command>
+{
+	Ok(Command::Progress {
+		anchor:     parse_anchor(t)?,
+		x:          parse_number(t, "x", 0.0)?,
+		y:          parse_number(t, "y", 0.0)?,
+		w:          parse_number(t, "w", 0.0)?,
+		h:          parse_number(t, "h", 0.0)?,
+		value:      parse_number(t, "value", 0.0)?,
+		color:      parse_color(t, "color", Color::WHITE)?,
+		background: parse_color(t, "background", Color::RGBA(0, 0, 0, 0))?,
+	})
+}
+
+// -----------------------------------------------------------------------------
+// Lua Functions
+// -----------------------------------------------------------------------------
+
+fn push(data: rlua::LightUserData, cmd: Command) -> LuaResult<()>
+{
+	let StateData { draw, .. }: &mut StateData = unsafe { &mut *(data.0 as *mut StateData) };
+
+	let layer: &mut Layer = draw.as_mut().ok_or_else(|| {
+		LuaError::RuntimeError("Drawing is only possible from within \"on_draw\".".to_owned())
+	})?;
+
+	layer.push(cmd);
+	Ok(())
+}
+
+pub fn load_defaults(ctx: &rlua::Context) -> LuaResult<()>
+{
+	let draw_text = ctx.create_function(|_, (data, t): (rlua::LightUserData, LuaTable)| {
+		push(data, parse_text(&t)?)
+	})?;
+
+	let draw_rect = ctx.create_function(|_, (data, t): (rlua::LightUserData, LuaTable)| {
+		push(data, parse_rect(&t)?)
+	})?;
+
+	let draw_piece = ctx.create_function(|_, (data, t): (rlua::LightUserData, LuaTable)| {
+		push(data, parse_piece(&t)?)
+	})?;
+
+	let draw_progress = ctx.create_function(|_, (data, t): (rlua::LightUserData, LuaTable)| {
+		push(data, parse_progress(&t)?)
+	})?;
+
+	let get_stats = ctx.create_function(|ctx, data: rlua::LightUserData| {
+		let StateData { game, .. }: &mut StateData = unsafe { &mut *(data.0 as *mut StateData) };
+
+		let t = ctx.create_table()?;
+		t.set("time", game.elapsed())?;
+		t.set("lines_cleared", game.lines_cleared)?;
+		t.set("pieces_placed", game.pieces_placed)?;
+		t.set("pps", game.pps())?;
+		t.set("score", game.score)?;
+		t.set("level", game.level())?;
+		t.set("width", game.field_size.0)?;
+		t.set("height", game.field_size.1)?;
+
+		Ok(t)
+	})?;
+
+	let g = ctx.globals();
+	g.set("_drawText", draw_text)?;
+	g.set("_drawRect", draw_rect)?;
+	g.set("_drawPiece", draw_piece)?;
+	g.set("_drawProgress", draw_progress)?;
+	g.set("_getStats", get_stats)?;
+
+	Ok(())
+}
@@ -0,0 +1,173 @@
+use std::path::PathBuf;
+
+use ini::Ini;
+use log::info;
+use rlua::prelude::*;
+
+use super::parse_pattern;
+use crate::{error::Error, lua::SearchPaths, menu::profile::load_property};
+
+/// Directory inside the profile and library directories holding piece sets.
+pub const PIECES_DIR: &str = "pieces";
+
+/// Prefix of the keys declaring palette entries of a piece, like `color.a = 255, 0, 0`.
+const PALETTE_PREFIX: &str = "color.";
+
+// -----------------------------------------------------------------------------
+// Piece Set Files
+// -----------------------------------------------------------------------------
+
+/// Returns the file of a piece set, preferring sets shipped with the profile and its parents over
+/// the library.
+fn set_file(paths: &SearchPaths, name: &str) -> Option<PathBuf>
+{
+	if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+	{
+		return None;
+	}
+
+	let file = format!("{}.ini", name);
+
+	std::iter::once(&paths.profile)
+		.chain(&paths.parents)
+		.chain(std::iter::once(&paths.library))
+		.map(|d| d.join(PIECES_DIR).join(&file))
+		.find(|p| p.is_file())
+}
+
+/// Parses a color written as `r, g, b` or `r, g, b, a`.
+fn parse_color<'a>(ctx: rlua::Context<'a>, s: &str) -> Result<LuaTable<'a>, Error>
+{
+	let v = s.split(',').map(|c| c.trim().parse::<u8>()).collect::<Result<Vec<_>, _>>();
+
+	let rgba = match v.as_deref()
+	{
+		Ok(&[r, g, b]) => [r, g, b, 0xFF],
+		Ok(&[r, g, b, a]) => [r, g, b, a],
+		_ => return Err(Error::from(format!("Invalid color \"{}\".", s))),
+	};
+
+	let t = ctx.create_table()?;
+	for (c, v) in ["r", "g", "b", "a"].iter().zip(rgba.iter())
+	{
+		t.set(*c, *v)?;
+	}
+
+	Ok(t)
+}
+
+/// Converts a section of a piece set into the table format used by scripts.
+fn parse_piece<'a>(
+	ctx: rlua::Context<'a>, name: &str, sec: &ini::Properties,
+) -> Result<LuaTable<'a>, Error>
+{
+	let t = ctx.create_table()?;
+	t.set("name", name)?;
+
+	let size: u32 =
+		load_property(sec, "size").ok_or_else(|| Error::from("Missing or invalid \"size\"."))?;
+	t.set("size", size)?;
+
+	let template = sec.get("template").ok_or_else(|| Error::from("Missing \"template\"."))?;
+	t.set("template", template)?;
+
+	if let Some(c) = sec.get("color")
+	{
+		t.set("color", parse_color(ctx, c)?)?;
+	}
+
+	if let Some(tile) = sec.get("tile")
+	{
+		let tile: u32 = tile.parse().map_err(|_| Error::from("Invalid \"tile\"."))?;
+		t.set("tile", tile)?;
+	}
+
+	let palette = ctx.create_table()?;
+	for (k, v) in sec.iter()
+	{
+		if let Some(key) = k.strip_prefix(PALETTE_PREFIX)
+		{
+			let c = parse_color(ctx, v)?;
+
+			match key.parse::<u8>()
+			{
+				Ok(i) => palette.set(i, c)?,
+				Err(_) => palette.set(key, c)?,
+			}
+		}
+	}
+	t.set("colors", palette)?;
+
+	// Reject pieces the game wouldn't accept when they are loaded, not when they spawn
+	parse_pattern(t.clone())?;
+
+	Ok(t)
+}
+
+/// Loads the named piece set as a list of piece tables. Every section of the set file declares a
+/// piece:
+///
+/// ```ini
+/// [T]
+/// size = 3
+/// template = 010 111 000
+/// color = 142, 47, 186
+/// ```
+pub fn load_set<'a>(
+	ctx: rlua::Context<'a>, paths: &SearchPaths, name: &str,
+) -> Result<LuaTable<'a>, Error>
+{
+	let file = set_file(paths, name)
+		.ok_or_else(|| Error::from(format!("Unknown piece set \"{}\".", name)))?;
+
+	info!("Loading piece set \"{}\".", file.display());
+
+	let ini = Ini::load_from_file(&file)?;
+	let set = ctx.create_table()?;
+	let mut count = 0;
+
+	for (piece, sec) in ini.iter()
+	{
+		let piece = match piece
+		{
+			Some(p) => p,
+			None => continue,
+		};
+
+		let t = parse_piece(ctx, piece, sec).map_err(|e| {
+			Error::from(format!(
+				"Piece \"{}\" of set \"{}\": {}",
+				piece,
+				name,
+				e.message()
+			))
+		})?;
+
+		count += 1;
+		set.set(count, t)?;
+	}
+
+	if count == 0
+	{
+		return Err(Error::from(format!("Piece set \"{}\" is empty.", name)));
+	}
+
+	Ok(set)
+}
+
+// -----------------------------------------------------------------------------
+// Lua Functions
+// -----------------------------------------------------------------------------
+
+pub fn load_defaults(ctx: &rlua::Context, paths: &SearchPaths) -> LuaResult<()>
+{
+	let paths = paths.clone();
+
+	let piece_set = ctx.create_function(move |ctx, name: String| {
+		load_set(ctx, &paths, &name).map_err(|e| LuaError::RuntimeError(e.message().to_owned()))
+	})?;
+
+	ctx.globals().set("_pieceSet", piece_set)?;
+
+	Ok(())
+}
@@ -0,0 +1,163 @@
+use rlua::prelude::*;
+
+use super::{call_lua, parse_pattern, StateData};
+use crate::{
+	error::Error,
+	lua::find_string,
+	runtime::{
+		state::randomizer::{Kind, Randomizer},
+		Framework, Piece, TetrisState,
+	},
+};
+
+/// Registry list of the pieces the randomizer chooses from.
+const PIECES_KEY: &str = "dedtris.pieces";
+
+// -----------------------------------------------------------------------------
+// Randomizer Parsing
+// -----------------------------------------------------------------------------
+
+fn parse_kind(name: &str, t: Option<&LuaTable>) -> Result<Kind, Error>
+{
+	let option = |key: &str, default: usize| -> Result<usize, Error> {
+		match t
+		{
+			Some(t) => Ok(t.get::<_, Option<usize>>(key)?.unwrap_or(default)),
+			None => Ok(default),
+		}
+	};
+
+	match name
+	{
+		"random" => Ok(Kind::Random),
+		"bag" | "7-bag" => Ok(Kind::Bag {
+			copies: option("copies", 1)?,
+		}),
+		"14-bag" => Ok(Kind::Bag {
+			copies: option("copies", 2)?,
+		}),
+		"history" | "tgm" => Ok(Kind::History {
+			size:  option("history", 4)?,
+			rolls: option("rolls", 4)?,
+		}),
+		_ => Err(Error::from(format!(
+			"Unknown randomizer \"{}\", expected \"random\", \"bag\", \"14-bag\" or \"history\".",
+			name
+		))),
+	}
+}
+
+/// Parses the optional "pieces" and "randomizer" fields of `init_game`:
+///
+/// ```lua
+/// pieces = _pieceSet("tetrominoes"),
+/// randomizer = { kind = "history", history = 4, rolls = 6, no_first = { "S", "Z" } },
+/// ```
+///
+/// The randomizer can also be given by name only and defaults to "random".
+pub fn parse_randomizer<'a>(
+	ctx: &rlua::Context<'a>, init: &LuaTable<'a>,
+) -> Result<Option<Randomizer>, Error>
+{
+	let pieces = match init.get::<_, Option<LuaTable>>("pieces")?
+	{
+		Some(p) => p,
+		None if init.contains_key("randomizer")? =>
+		{
+			return Err(Error::from("A randomizer needs a list of \"pieces\"."))
+		},
+		None => return Ok(None),
+	};
+
+	let mut names = Vec::new();
+	for p in pieces.clone().sequence_values::<LuaTable>()
+	{
+		let p = p?;
+		names.push(p.get::<_, Option<String>>("name")?);
+		parse_pattern(p)?;
+	}
+
+	if names.is_empty()
+	{
+		return Err(Error::from("The list of \"pieces\" is empty."));
+	}
+
+	let (kind, no_first) = match init.get::<_, LuaValue>("randomizer")?
+	{
+		LuaValue::Nil => (Kind::Random, Vec::new()),
+		LuaValue::String(s) => (parse_kind(s.to_str()?, None)?, Vec::new()),
+		LuaValue::Table(t) =>
+		{
+			let kind = parse_kind(find_string(&t, "kind")?.to_str()?, Some(&t))?;
+			let no_first = t.get::<_, Option<Vec<String>>>("no_first")?.unwrap_or_default();
+			(kind, no_first)
+		},
+		_ => return Err(Error::from("\"randomizer\" must be a name or a table.")),
+	};
+
+	let mut avoided = Vec::with_capacity(no_first.len());
+	for n in no_first
+	{
+		match names.iter().position(|p| p.as_deref() == Some(n.as_str()))
+		{
+			Some(i) => avoided.push(i),
+			None => return Err(Error::from(format!("There is no piece named \"{}\".", n))),
+		}
+	}
+
+	ctx.set_named_registry_value(PIECES_KEY, pieces)?;
+
+	Ok(Some(Randomizer::new(kind, names.len(), avoided)))
+}
+
+// -----------------------------------------------------------------------------
+// Piece Spawning
+// -----------------------------------------------------------------------------
+
+/// Draws the table of the next piece from the randomizer.
+pub fn next_piece_table<'a>(ctx: rlua::Context<'a>, r: &mut Randomizer) -> LuaResult<LuaTable<'a>>
+{
+	let pieces: LuaTable = ctx.named_registry_value(PIECES_KEY)?;
+	pieces.get(r.next() + 1)
+}
+
+/// Asks the "spawn_piece" hook for the next piece, or the randomizer if the profile doesn't
+/// define the hook.
+pub fn call_spawn(
+	state: &mut TetrisState, fw: &Framework<'_, '_, '_, '_, '_>,
+) -> Result<Piece, Error>
+{
+	let ctx = fw.lua;
+
+	if ctx.globals().get::<_, Option<LuaFunction>>("spawn_piece")?.is_some()
+	{
+		return parse_pattern(call_lua("spawn_piece", state, fw)?);
+	}
+
+	let r = state.randomizer.as_mut().ok_or_else(|| {
+		Error::from("The profile needs either a \"spawn_piece\" function or a randomizer.")
+	})?;
+
+	parse_pattern(next_piece_table(*ctx, r)?)
+}
+
+// -----------------------------------------------------------------------------
+// Lua Functions
+// -----------------------------------------------------------------------------
+
+pub fn load_defaults(ctx: &rlua::Context) -> LuaResult<()>
+{
+	let next_piece = ctx.create_function(|ctx, data: rlua::LightUserData| {
+		let StateData { game, .. }: &mut StateData = unsafe { &mut *(data.0 as *mut StateData) };
+
+		let r = game.randomizer.as_mut().ok_or_else(|| {
+			LuaError::RuntimeError("The profile doesn't declare a randomizer.".to_owned())
+		})?;
+
+		next_piece_table(ctx, r)
+	})?;
+
+	ctx.globals().set("_nextPiece", next_piece)?;
+
+	Ok(())
+}
@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use sdl2::{
+	event::Event,
+	pixels::Color,
+	rect::{Point, Rect},
+	EventPump,
+};
+
+use super::{drawer::font, game::Game, state::Sample};
+use crate::{
+	menu::{self, Input},
+	storage::{self, Record},
+};
+
+const BG_COLOR: Color = Color::RGBA(0, 0, 0, 230);
+const TEXT_COLOR: Color = Color::RGB(200, 200, 200);
+const SELECTED_COLOR: Color = Color::RGB(242, 210, 68);
+const LINES_COLOR: Color = Color::RGB(68, 210, 242);
+const PPS_COLOR: Color = Color::RGB(242, 210, 68);
+
+const SCALE: u32 = 2;
+const MARGIN: i32 = 40;
+
+/// What to do after a game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome
+{
+	Retry,
+	Menu,
+	Quit,
+}
+
+const ACTIONS: [(&str, Outcome); 2] = [("Retry", Outcome::Retry), ("Back to menu", Outcome::Menu)];
+
+/// Final stats of a game.
+pub struct Results
+{
+	pub time:     f64,
+	pub record:   Record,
+	/// Personal bests before this game, if any.
+	pub best:     Option<Record>,
+	pub samples:  Vec<Sample>,
+	/// Whether the game ended by itself and counts towards the personal bests, rather than being
+	/// abandoned.
+	pub finished: bool,
+}
+
+fn format_time(t: f64) -> String
+{
+	format!("{}:{:05.2}", (t / 60.0) as u64, t % 60.0)
+}
+
+/// Compares a result to the personal best, higher being better.
+fn compare<T: PartialOrd>(v: T, best: Option<T>, show: impl Fn(T) -> String) -> String
+{
+	match best
+	{
+		None => String::new(),
+		Some(b) if v > b => "  New best!".to_owned(),
+		Some(b) => format!("  Best: {}", show(b)),
+	}
+}
+
+fn summary(game: &Game, r: &Results) -> String
+{
+	let s = &game.state;
+	let best = r.best.as_ref().filter(|_| r.finished);
+	// The speed is only compared once enough pieces were placed for it to mean something
+	let best_pps = best.filter(|_| s.pieces_placed >= storage::MIN_PPS_PIECES);
+	let pps = compare(r.record.pps, best_pps.map(|b| b.pps), |v| {
+		format!("{:.2}", v)
+	});
+
+	let title = if r.finished
+	{
+		game.rend.title.clone()
+	}
+	else
+	{
+		format!("{} (abandoned)", game.rend.title)
+	};
+
+	format!(
+		"{}\n\nScore   {}{}\nLines   {}{}\nPPS     {:.2}{}\nPieces  {}\nLevel   {}\nTime    {}",
+		title,
+		r.record.score,
+		compare(r.record.score, best.map(|b| b.score), |v| v.to_string()),
+		r.record.lines,
+		compare(r.record.lines, best.map(|b| b.lines), |v| v.to_string()),
+		r.record.pps,
+		pps,
+		s.pieces_placed,
+		s.level(),
+		format_time(r.time),
+	)
+}
+
+/// Plots lines cleared and pieces per second over time, each scaled to the height of the area.
+fn draw_graph(game: &mut Game, area: Rect, samples: &[Sample], time: f64)
+{
+	let canvas = &mut game.fw.canvas;
+
+	canvas.set_draw_color(Color::RGB(24, 24, 32));
+	canvas.fill_rect(area).unwrap();
+	canvas.set_draw_color(TEXT_COLOR);
+	canvas.draw_rect(area).unwrap();
+
+	if samples.len() < 2 || time <= 0.0
+	{
+		return;
+	}
+
+	let max_lines = samples.iter().map(|s| s.lines).max().unwrap_or(0).max(1) as f64;
+	let max_pps = samples.iter().map(|s| s.pps).fold(0.0, f64::max).max(0.01);
+
+	let plot = |value: &dyn Fn(&Sample) -> f64| -> Vec<Point> {
+		samples
+			.iter()
+			.map(|s| {
+				Point::new(
+					area.x + (s.time / time * (area.w - 1) as f64) as i32,
+					area.bottom() - 1 - (value(s) * (area.h - 1) as f64) as i32,
+				)
+			})
+			.collect()
+	};
+
+	canvas.set_draw_color(LINES_COLOR);
+	canvas.draw_lines(&plot(&|s| s.lines as f64 / max_lines)[..]).unwrap();
+
+	canvas.set_draw_color(PPS_COLOR);
+	canvas.draw_lines(&plot(&|s| s.pps / max_pps)[..]).unwrap();
+}
+
+fn draw(game: &mut Game, r: &Results, selected: usize)
+{
+	let (w, h) = game.fw.canvas.window().size();
+	let line = (font::LINE_ADVANCE * SCALE) as i32;
+
+	let canvas = &mut game.fw.canvas;
+	canvas.set_draw_color(BG_COLOR);
+	canvas.fill_rect(Rect::new(0, 0, w, h)).unwrap();
+
+	let text = summary(game, r);
+	let rend = &mut game.rend;
+	let canvas = &mut game.fw.canvas;
+
+	rend.draw_text(
+		canvas,
+		Point::new(MARGIN, MARGIN),
+		"Results",
+		2 * SCALE,
+		Color::WHITE,
+	);
+
+	let top = MARGIN + 3 * line;
+	rend.draw_text(canvas, Point::new(MARGIN, top), &text, SCALE, TEXT_COLOR);
+
+	let (_, text_h) = font::text_size(&text, SCALE);
+	let mut y = top + text_h as i32 + line;
+
+	for (i, (name, _)) in ACTIONS.iter().enumerate()
+	{
+		let (text, color) = if i == selected
+		{
+			(format!("> {}", name), SELECTED_COLOR)
+		}
+		else
+		{
+			(format!("  {}", name), TEXT_COLOR)
+		};
+
+		rend.draw_text(canvas, Point::new(MARGIN, y), &text, SCALE, color);
+		y += line;
+	}
+
+	// Graph on the right half
+	let x = w as i32 / 2;
+	let legend = Point::new(x, top);
+	rend.draw_text(canvas, legend, "Lines", SCALE, LINES_COLOR);
+	rend.draw_text(
+		canvas,
+		Point::new(x + 8 * line, top),
+		"PPS",
+		SCALE,
+		PPS_COLOR,
+	);
+
+	let gw = (w as i32 / 2 - MARGIN).max(1) as u32;
+	let gh = (h as i32 - top - line - 2 * MARGIN).max(1) as u32;
+	draw_graph(
+		game,
+		Rect::new(x, top + 2 * line, gw, gh),
+		&r.samples,
+		r.time,
+	);
+}
+
+/// Shows the results of a finished game until the player picks what to do next.
+pub fn show(game: &mut Game, event_pump: &mut EventPump, r: &Results) -> Outcome
+{
+	let mut selected = 0;
+
+	loop
+	{
+		for event in event_pump.poll_iter()
+		{
+			if let Event::Quit { .. } = event
+			{
+				return Outcome::Quit;
+			}
+
+			match menu::input(&event)
+			{
+				Some(Input::Up) => selected = (selected + ACTIONS.len() - 1) % ACTIONS.len(),
+				Some(Input::Down) => selected = (selected + 1) % ACTIONS.len(),
+				Some(Input::Select) => return ACTIONS[selected].1,
+				Some(Input::Back) => return Outcome::Menu,
+				None => (),
+			}
+		}
+
+		draw(game, r, selected);
+		game.fw.canvas.present();
+
+		::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 30));
+	}
+}
@@ -10,6 +10,7 @@ use crate::error::Error;
 pub mod field;
 pub mod flags;
 pub mod pieces;
+pub mod randomizer;
 
 #[derive(Debug)]
 pub enum Direction
@@ -19,6 +20,59 @@ pub enum Direction
 	RIGHT,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum Rotation
+{
+	Clockwise,
+	CounterClockwise,
+}
+
+/// Pauses between placing a piece and spawning the next one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Delays
+{
+	/// Before every spawn, also known as ARE.
+	pub are:        Duration,
+	/// Added to `are` when the piece cleared lines.
+	pub line_clear: Duration,
+}
+
+/// Lines removed from the field at once, with the blocks they held.
+#[derive(Debug, Clone)]
+pub struct Clear
+{
+	pub rows:   Vec<i32>,
+	pub blocks: Vec<Point>,
+	pub colors: Vec<Color>,
+	pub tiles:  Vec<u32>,
+}
+
+/// Notable moments of the game, played back as animations and particles.
+#[derive(Debug, Clone)]
+pub enum GameEvent
+{
+	LineClear(Clear),
+	/// Lines were cleared, leaving the field empty.
+	PerfectClear,
+	/// Reported by a script through `_event` at a position on the field, like T-spins which the
+	/// engine doesn't detect itself.
+	Custom
+	{
+		name: String,
+		x:    f64,
+		y:    f64,
+	},
+}
+
+/// Stats at the time a piece was placed.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample
+{
+	pub time:  f64,
+	pub lines: u64,
+	pub pps:   f64,
+}
+
 pub struct TetrisState
 {
 	// Flags
@@ -27,12 +81,19 @@ pub struct TetrisState
 	// Field
 	pub field_blocks: Vec<Point>,
 	pub field_colors: Vec<Color>,
+	pub field_tiles:  Vec<u32>,
 	pub field_size:   Size,
 
+	/// Tile of blocks set by scripts without one.
+	pub garbage_tile: u32,
+
 	// Piece queue
 	pub piece_queue:     Vec<Piece>,
 	pub piece_queue_idx: usize,
 
+	// Piece randomizer, if the engine chooses the pieces
+	pub randomizer: Option<randomizer::Randomizer>,
+
 	// Piece swap
 	pub piece_swap: Option<Piece>,
 
@@ -45,21 +106,42 @@ pub struct TetrisState
 	pub player_tick_dur:  Duration,
 	pub player_tick_time: Instant,
 
+	// Delays, the next piece spawns at `spawn_time` if set
+	pub delays:     Delays,
+	pub spawn_time: Option<Instant>,
+
+	/// Events since the game last looked, to animate them.
+	pub events: Vec<GameEvent>,
+
 	// Stats
 	pub time:          Instant,
+	/// When the game ended, freezing the elapsed time.
+	pub end_time:      Option<Instant>,
 	pub lines_cleared: u64,
 	pub pieces_placed: u64,
+	pub score:         u64,
+	pub samples:       Vec<Sample>,
 
 	// Exit through Lua
 	pub exit: bool,
 }
 
+/// Points for clearing `n` lines at once on level one.
+fn line_score(n: usize) -> u64
+{
+	const SCORES: [u64; 5] = [0, 100, 300, 500, 800];
+
+	SCORES.get(n).copied().unwrap_or(200 * n as u64)
+}
+
 pub fn init_game(
-	field_dim: Size, start_piece: Piece, piece_hold_enabled: bool, player_tick: Duration,
+	field_dim: Size, start_piece: Piece, randomizer: Option<randomizer::Randomizer>,
+	piece_hold_enabled: bool, player_tick: Duration,
 ) -> Result<TetrisState, Error>
 {
 	let field_blocks = Vec::new();
 	let field_colors = Vec::new();
+	let field_tiles = Vec::new();
 	let field_size = field_dim;
 
 	let player = match pieces::spawn_piece(&field_blocks, field_dim, start_piece)
@@ -78,11 +160,16 @@ pub fn init_game(
 
 		field_blocks,
 		field_colors,
+		field_tiles,
 		field_size,
 
+		garbage_tile: 0,
+
 		piece_queue: Vec::new(),
 		piece_queue_idx: 0,
 
+		randomizer,
+
 		piece_swap: None,
 
 		player_proj: player.proj,
@@ -92,9 +179,17 @@ pub fn init_game(
 		player_tick_dur: player_tick,
 		player_tick_time,
 
+		delays: Delays::default(),
+		spawn_time: None,
+
+		events: Vec::new(),
+
 		time: Instant::now(),
+		end_time: None,
 		lines_cleared: 0,
 		pieces_placed: 0,
+		score: 0,
+		samples: Vec::new(),
 
 		exit: false,
 	})
@@ -106,33 +201,140 @@ impl TetrisState
 	{
 		let fb = &mut self.field_blocks;
 		let fc = &mut self.field_colors;
+		let ft = &mut self.field_tiles;
 		let fs = self.field_size;
 
-		let lines = field::clear_lines(fs, fb, fc);
+		let rows = field::lines_list(fs, fb);
+		if !rows.is_empty()
+		{
+			let mut clear = Clear {
+				rows,
+				blocks: Vec::new(),
+				colors: Vec::new(),
+				tiles: Vec::new(),
+			};
+
+			for ((b, c), t) in fb.iter().zip(fc.iter()).zip(ft.iter())
+			{
+				if clear.rows.contains(&b.y)
+				{
+					clear.blocks.push(*b);
+					clear.colors.push(*c);
+					clear.tiles.push(*t);
+				}
+			}
+
+			self.events.push(GameEvent::LineClear(clear));
+		}
+
+		let lines = field::clear_lines(fs, fb, fc, ft);
+
+		if !lines.is_empty() && self.field_blocks.is_empty()
+		{
+			self.events.push(GameEvent::PerfectClear);
+		}
+
+		self.score += line_score(lines.len()) * self.level();
 		self.lines_cleared += lines.len() as u64;
 
 		lines
 	}
 
-	pub fn rotate(&mut self) -> bool
+	/// Level of the game, rising every ten cleared lines.
+	pub fn level(&self) -> u64
 	{
+		self.lines_cleared / 10 + 1
+	}
+
+	/// Seconds played, up to the end of the game once it ended.
+	pub fn elapsed(&self) -> f64
+	{
+		let end = self.end_time.unwrap_or_else(Instant::now);
+		end.saturating_duration_since(self.time).as_secs_f64()
+	}
+
+	/// Whether the game waits for the next piece to spawn, the player piece being out of play.
+	pub fn waiting(&self) -> bool
+	{
+		self.spawn_time.is_some()
+	}
+
+	/// Stops the clock of the game.
+	pub fn finish(&mut self)
+	{
+		self.end_time.get_or_insert_with(Instant::now);
+	}
+
+	/// Pieces placed per second.
+	pub fn pps(&self) -> f64
+	{
+		let t = self.elapsed();
+		if t > 0.0
+		{
+			self.pieces_placed as f64 / t
+		}
+		else
+		{
+			0.0
+		}
+	}
+
+	/// Remembers the current stats for the graphs of the results screen.
+	pub fn record_sample(&mut self)
+	{
+		self.samples.push(Sample {
+			time:  self.elapsed(),
+			lines: self.lines_cleared,
+			pps:   self.pps(),
+		});
+	}
+
+	/// Replaces the blocks on the field.
+	pub fn set_field(&mut self, blocks: Vec<Point>, colors: Vec<Color>, tiles: Vec<u32>)
+	{
+		self.field_blocks = blocks;
+		self.field_colors = colors;
+		self.field_tiles = tiles;
+
+		let fs = self.field_size;
 		let p = &self.player_piece;
+		self.player_proj = pieces::project(fs, &self.field_blocks, self.player_pos, &p.blocks);
+	}
 
-		let new_pblocks: Vec<Point> =
-			p.blocks.iter().map(|b| Point::new(p.dim as i32 - 1 - b.y, b.x)).collect();
+	pub fn rotate(&mut self, dir: Rotation) -> bool
+	{
+		let r = pieces::rotate(&self.player_piece, dir);
+		self.apply_rotation(r)
+	}
 
+	/// Moves the player piece into the rotated orientation if it fits.
+	pub fn apply_rotation(&mut self, r: pieces::Rotated) -> bool
+	{
 		let fb = &self.field_blocks;
 		let fs = self.field_size;
-		let pp = self.player_pos;
+		let pp = Point::new(
+			self.player_pos.x + r.offset.x,
+			self.player_pos.y + r.offset.y,
+		);
 
-		if !field::check_valid_pos(fs, fb, pp, &new_pblocks)
+		if !field::check_valid_pos(fs, fb, pp, &r.blocks)
 		{
 			return false;
 		}
 
-		let p = pieces::project(fs, fb, pp, &new_pblocks);
+		let p = pieces::project(fs, fb, pp, &r.blocks);
+
+		let piece = &mut self.player_piece;
+		piece.blocks = r.blocks;
+		piece.rotation = r.rotation;
+
+		if let Some(s) = r.style
+		{
+			piece.colors = s.0;
+			piece.tiles = s.1;
+		}
 
-		self.player_piece.blocks = new_pblocks;
+		self.player_pos = pp;
 		self.player_proj = p;
 
 		true
@@ -171,13 +373,14 @@ impl TetrisState
 	pub fn output_score(&self)
 	{
 		println!(
-			"Well done! Here are your stats.\nScore: {}\nTime: {}\nLines cleared: {}\nPieces \
-			 placed: {}",
-			self.lines_cleared as f64
-				/ (self.time.elapsed().as_secs_f64() * self.pieces_placed as f64),
-			self.time.elapsed().as_secs_f64(),
+			"Well done! Here are your stats.\nScore: {}\nLevel: {}\nTime: {}\nLines cleared: \
+			 {}\nPieces placed: {}\nPieces per second: {:.2}",
+			self.score,
+			self.level(),
+			self.elapsed(),
 			self.lines_cleared,
 			self.pieces_placed,
+			self.pps(),
 		);
 	}
 
@@ -21,7 +21,9 @@ pub fn lines_list(fd: Size, fb: &[Point]) -> Vec<i32>
 		.collect()
 }
 
-pub fn clear_lines(fd: Size, fb: &mut Vec<Point>, fc: &mut Vec<Color>) -> Vec<i32>
+pub fn clear_lines(
+	fd: Size, fb: &mut Vec<Point>, fc: &mut Vec<Color>, ft: &mut Vec<u32>,
+) -> Vec<i32>
 {
 	info!("Removing lines from field.");
 
@@ -38,6 +40,7 @@ pub fn clear_lines(fd: Size, fb: &mut Vec<Point>, fc: &mut Vec<Color>) -> Vec<i3
 			{
 				fb.swap_remove(i);
 				fc.swap_remove(i);
+				ft.swap_remove(i);
 				removed += 1;
 			}
 			else
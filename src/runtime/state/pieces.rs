@@ -1,6 +1,6 @@
-use sdl2::rect::Point;
+use sdl2::{pixels::Color, rect::Point};
 
-use super::{field, Size};
+use super::{field, Rotation, Size};
 use crate::runtime::Piece;
 
 // -----------------------------------------------------------------------------
@@ -39,3 +39,66 @@ pub fn spawn_piece(field_blocks: &[Point], field_dim: Size, piece: Piece) -> Opt
 
 	Some(SpawnedPiece { piece, proj, pos })
 }
+
+// -----------------------------------------------------------------------------
+// Rotation
+// -----------------------------------------------------------------------------
+
+pub struct Rotated
+{
+	pub blocks:   Vec<Point>,
+	pub offset:   Point,
+	pub rotation: usize,
+
+	/// Colors and tiles of the blocks if the orientation changes them.
+	pub style: Option<(Vec<Color>, Vec<u32>)>,
+}
+
+/// Rotates a piece into its next orientation. Pieces without explicit orientations are rotated
+/// about their bounding box.
+pub fn rotate(piece: &Piece, dir: Rotation) -> Rotated
+{
+	let n = if piece.rotations.is_empty()
+	{
+		4
+	}
+	else
+	{
+		piece.rotations.len()
+	};
+
+	let rotation = match dir
+	{
+		Rotation::Clockwise => (piece.rotation + 1) % n,
+		Rotation::CounterClockwise => (piece.rotation + n - 1) % n,
+	};
+
+	let (blocks, style) = if piece.rotations.is_empty()
+	{
+		let d = piece.dim as i32 - 1;
+
+		let blocks = piece
+			.blocks
+			.iter()
+			.map(|b| match dir
+			{
+				Rotation::Clockwise => Point::new(d - b.y, b.x),
+				Rotation::CounterClockwise => Point::new(b.y, d - b.x),
+			})
+			.collect();
+
+		(blocks, None)
+	}
+	else
+	{
+		let s = piece.rotations[rotation].clone();
+		(s.blocks, Some((s.colors, s.tiles)))
+	};
+
+	Rotated {
+		blocks,
+		offset: Point::new(0, 0),
+		rotation,
+		style,
+	}
+}
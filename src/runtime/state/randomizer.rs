@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// How the next piece of a piece set is chosen.
+#[derive(Debug, Clone, Copy)]
+pub enum Kind
+{
+	/// Every piece is equally likely on every draw.
+	Random,
+	/// Draws from a shuffled bag holding every piece `copies` times, refilled when empty.
+	Bag
+	{
+		copies: usize
+	},
+	/// Rerolls up to `rolls` times while the piece is among the last `size` pieces, like the TGM
+	/// series.
+	History
+	{
+		size: usize, rolls: usize
+	},
+}
+
+#[derive(Debug)]
+pub struct Randomizer
+{
+	kind:  Kind,
+	count: usize,
+
+	/// Pieces the first draw avoids, like S and Z which can't be placed without holes.
+	no_first: Vec<usize>,
+	first:    bool,
+
+	bag:     Vec<usize>,
+	history: VecDeque<usize>,
+
+	rng: StdRng,
+}
+
+impl Randomizer
+{
+	/// Creates a randomizer choosing from `count` pieces, identified by their index.
+	pub fn new(kind: Kind, count: usize, no_first: Vec<usize>) -> Self
+	{
+		let mut history = VecDeque::new();
+
+		// The history starts filled with the avoided pieces, making them unlikely early on
+		if let Kind::History { size, .. } = kind
+		{
+			history.extend(no_first.iter().cycle().take(size));
+		}
+
+		Randomizer {
+			kind,
+			count: count.max(1),
+			no_first,
+			first: true,
+			bag: Vec::new(),
+			history,
+			rng: StdRng::from_entropy(),
+		}
+	}
+
+	/// Returns the index of the next piece.
+	pub fn next(&mut self) -> usize
+	{
+		let first = std::mem::replace(&mut self.first, false);
+
+		let avoided = if first
+		{
+			self.no_first.clone()
+		}
+		else
+		{
+			Vec::new()
+		};
+		let allowed = |i: &usize| !avoided.contains(i);
+
+		match self.kind
+		{
+			Kind::Random => self.pick(&avoided),
+
+			Kind::Bag { copies } =>
+			{
+				if self.bag.is_empty()
+				{
+					self.bag = (0..self.count * copies.max(1)).map(|i| i % self.count).collect();
+					self.bag.shuffle(&mut self.rng);
+				}
+
+				let i = self.bag.iter().rposition(allowed).unwrap_or(self.bag.len() - 1);
+				self.bag.swap_remove(i)
+			},
+
+			Kind::History { size, rolls } =>
+			{
+				let mut p = self.pick(&avoided);
+
+				for _ in 1..rolls
+				{
+					if !self.history.contains(&p)
+					{
+						break;
+					}
+
+					p = self.pick(&avoided);
+				}
+
+				self.history.push_back(p);
+				while self.history.len() > size
+				{
+					self.history.pop_front();
+				}
+
+				p
+			},
+		}
+	}
+
+	/// Picks a random piece, skipping `avoided` unless every piece is avoided.
+	fn pick(&mut self, avoided: &[usize]) -> usize
+	{
+		let candidates: Vec<usize> = (0..self.count).filter(|i| !avoided.contains(i)).collect();
+
+		match candidates.choose(&mut self.rng)
+		{
+			Some(p) => *p,
+			None => self.rng.gen_range(0..self.count),
+		}
+	}
+}
@@ -0,0 +1,187 @@
+//! Snapshot tests drawing canned game states into a software canvas and comparing them to the
+//! reference images in `tests/snapshots`. Set `BLESS_SNAPSHOTS` to write the drawn frames as the
+//! new references after an intended change, or to add the reference of a new test.
+
+use std::{path::PathBuf, sync::Mutex, time::Duration};
+
+use sdl2::{
+	pixels::{Color, PixelFormatEnum},
+	rect::Point,
+	surface::Surface,
+};
+
+use super::{draw_state, drawer, state, Piece, TetrisState};
+
+const WIN_DIM: (u32, u32) = (320, 240);
+
+/// SDL renderers aren't meant to be used from several threads at once.
+static SDL: Mutex<()> = Mutex::new(());
+
+fn snapshot_dir() -> PathBuf
+{
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots")
+}
+
+fn piece(dim: u32, blocks: &[(i32, i32)], color: Color) -> Piece
+{
+	Piece {
+		dim,
+		colors: vec![color; blocks.len()],
+		tiles: vec![0; blocks.len()],
+		blocks: blocks.iter().map(|(x, y)| Point::new(*x, *y)).collect(),
+		rotations: Vec::new(),
+		rotation: 0,
+	}
+}
+
+fn t_piece() -> Piece
+{
+	piece(
+		3,
+		&[(1, 0), (0, 1), (1, 1), (2, 1)],
+		Color::RGB(142, 47, 186),
+	)
+}
+
+fn i_piece() -> Piece
+{
+	piece(
+		4,
+		&[(0, 1), (1, 1), (2, 1), (3, 1)],
+		Color::RGB(68, 210, 242),
+	)
+}
+
+fn o_piece() -> Piece
+{
+	piece(
+		2,
+		&[(0, 0), (1, 0), (0, 1), (1, 1)],
+		Color::RGB(242, 210, 68),
+	)
+}
+
+/// A new game with a frozen clock, so the HUD always shows the same stats.
+fn canned_state(hold: bool) -> TetrisState
+{
+	let mut s = state::init_game((10, 20), t_piece(), None, hold, Duration::from_secs(1)).unwrap();
+	s.end_time = Some(s.time);
+	s
+}
+
+/// Fills the bottom `rows` rows of the field, leaving a hole in every row.
+fn fill_rows(s: &mut TetrisState, rows: i32)
+{
+	let (w, h) = s.field_size;
+	let mut blocks = Vec::new();
+
+	for y in h as i32 - rows..h as i32
+	{
+		let hole = y % w as i32;
+		blocks.extend((0..w as i32).filter(|x| *x != hole).map(|x| Point::new(x, y)));
+	}
+
+	let colors = vec![Color::RGB(90, 160, 90); blocks.len()];
+	let tiles = vec![0; blocks.len()];
+	s.set_field(blocks, colors, tiles);
+}
+
+fn render(state: &TetrisState, error: Option<&str>) -> Surface<'static>
+{
+	let surface = Surface::new(WIN_DIM.0, WIN_DIM.1, PixelFormatEnum::ARGB8888).unwrap();
+	let mut canvas = surface.into_canvas().unwrap();
+	canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+
+	let tc = canvas.texture_creator();
+	let texture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Profiles/default/template.bmp");
+	let mut rend = drawer::init_renderer(&tc, WIN_DIM, &texture, None).unwrap();
+	rend.title = "Snapshot".to_owned();
+
+	canvas.set_draw_color(rend.bg_color);
+	canvas.clear();
+	draw_state(&mut rend, &mut canvas, state, error);
+	canvas.present();
+
+	canvas.into_surface()
+}
+
+fn pixels(s: &Surface) -> Vec<u8>
+{
+	let s = s.convert_format(PixelFormatEnum::ARGB8888).unwrap();
+	let (w, pitch) = (s.width() as usize * 4, s.pitch() as usize);
+
+	s.with_lock(|p| p.chunks(pitch).flat_map(|row| row[..w].to_vec()).collect())
+}
+
+fn assert_snapshot(name: &str, actual: &Surface)
+{
+	let file = snapshot_dir().join(format!("{}.bmp", name));
+
+	if std::env::var_os("BLESS_SNAPSHOTS").is_some()
+	{
+		std::fs::create_dir_all(snapshot_dir()).unwrap();
+		actual.save_bmp(&file).unwrap();
+		return;
+	}
+
+	let out = std::env::temp_dir().join(format!("{}.actual.bmp", name));
+
+	if !file.exists()
+	{
+		actual.save_bmp(&out).unwrap();
+
+		panic!(
+			"Snapshot \"{}\" has no reference image, the drawn frame was saved to \"{}\". Run the \
+			 tests with BLESS_SNAPSHOTS set to add it.",
+			name,
+			out.display()
+		);
+	}
+
+	let expected = Surface::load_bmp(&file).unwrap();
+	let matches = expected.size() == actual.size() && pixels(&expected) == pixels(actual);
+
+	if !matches
+	{
+		actual.save_bmp(&out).unwrap();
+
+		panic!(
+			"Snapshot \"{}\" differs, the drawn frame was saved to \"{}\".",
+			name,
+			out.display()
+		);
+	}
+}
+
+#[test]
+fn empty_field()
+{
+	let _lock = SDL.lock().unwrap_or_else(|e| e.into_inner());
+
+	let s = canned_state(false);
+	assert_snapshot("empty_field", &render(&s, None));
+}
+
+#[test]
+fn filled_field_with_queue_and_hold()
+{
+	let _lock = SDL.lock().unwrap_or_else(|e| e.into_inner());
+
+	let mut s = canned_state(true);
+	fill_rows(&mut s, 6);
+	s.piece_queue = vec![i_piece(), o_piece(), t_piece()];
+	s.piece_swap = Some(o_piece());
+
+	assert_snapshot("filled_field_with_queue_and_hold", &render(&s, None));
+}
+
+#[test]
+fn error_screen()
+{
+	let _lock = SDL.lock().unwrap_or_else(|e| e.into_inner());
+
+	let s = canned_state(false);
+	let error = "[string \"script.lua\"]:3: attempt to call a nil value (global 'spawn')";
+
+	assert_snapshot("error_screen", &render(&s, Some(error)));
+}
@@ -0,0 +1,60 @@
+use std::{
+	path::{Path, PathBuf},
+	time::{Duration, Instant, SystemTime},
+};
+
+/// Time between two checks of the watched files.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls the modification times of a set of files.
+pub struct Watcher
+{
+	files:      Vec<(PathBuf, Option<SystemTime>)>,
+	next_check: Instant,
+}
+
+fn modified(p: &Path) -> Option<SystemTime>
+{
+	std::fs::metadata(p).and_then(|m| m.modified()).ok()
+}
+
+impl Watcher
+{
+	pub fn new(files: Vec<PathBuf>) -> Self
+	{
+		let files = files.into_iter().map(|f| {
+			let t = modified(&f);
+			(f, t)
+		});
+
+		Watcher {
+			files:      files.collect(),
+			next_check: Instant::now() + CHECK_INTERVAL,
+		}
+	}
+
+	/// Returns true if a watched file was modified since the last check.
+	pub fn changed(&mut self) -> bool
+	{
+		let now = Instant::now();
+		if now < self.next_check
+		{
+			return false;
+		}
+
+		self.next_check = now + CHECK_INTERVAL;
+
+		let mut changed = false;
+		for (f, t) in &mut self.files
+		{
+			let new_t = modified(f);
+			if new_t != *t
+			{
+				*t = new_t;
+				changed = true;
+			}
+		}
+
+		changed
+	}
+}
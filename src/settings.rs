@@ -0,0 +1,340 @@
+use std::path::PathBuf;
+
+use ini::Ini;
+use log::{info, warn};
+
+use crate::{error::Error, menu::profile::load_property, storage};
+
+/// Section of the settings file holding the visual effects.
+const EFFECTS_SECTION: &str = "effects";
+
+/// Section of the settings file holding the window options and its last geometry.
+const WINDOW_SECTION: &str = "window";
+
+/// Section of the settings file holding the screenshot options.
+const SCREENSHOTS_SECTION: &str = "screenshots";
+
+/// Section of the settings file holding gameplay display options.
+const GAME_SECTION: &str = "game";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat
+{
+	Png,
+	Bmp,
+}
+
+impl ImageFormat
+{
+	pub const ALL: [ImageFormat; 2] = [ImageFormat::Png, ImageFormat::Bmp];
+
+	pub fn extension(self) -> &'static str
+	{
+		match self
+		{
+			ImageFormat::Png => "png",
+			ImageFormat::Bmp => "bmp",
+		}
+	}
+
+	fn parse(s: &str) -> Option<Self>
+	{
+		match s
+		{
+			"png" => Some(ImageFormat::Png),
+			"bmp" => Some(ImageFormat::Bmp),
+			_ => None,
+		}
+	}
+}
+
+/// How the ghost piece, the preview of where the player piece lands, is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GhostMode
+{
+	Off,
+	/// The piece at half opacity.
+	Translucent,
+	/// The outline of the piece in its colors.
+	Outline,
+	/// Untextured blocks in the colors of the piece.
+	Colored,
+	/// The ghost tile of the skin, translucent if the skin has none.
+	Tile,
+}
+
+impl GhostMode
+{
+	pub const ALL: [GhostMode; 5] = [
+		GhostMode::Off,
+		GhostMode::Translucent,
+		GhostMode::Outline,
+		GhostMode::Colored,
+		GhostMode::Tile,
+	];
+
+	pub fn name(self) -> &'static str
+	{
+		match self
+		{
+			GhostMode::Off => "off",
+			GhostMode::Translucent => "translucent",
+			GhostMode::Outline => "outline",
+			GhostMode::Colored => "colored",
+			GhostMode::Tile => "tile",
+		}
+	}
+
+	fn parse(s: &str) -> Option<Self>
+	{
+		match s
+		{
+			"off" => Some(GhostMode::Off),
+			"translucent" => Some(GhostMode::Translucent),
+			"outline" => Some(GhostMode::Outline),
+			"colored" => Some(GhostMode::Colored),
+			"tile" => Some(GhostMode::Tile),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowMode
+{
+	Windowed,
+	/// Exclusive fullscreen at the window size.
+	Fullscreen,
+	/// A borderless window covering the desktop.
+	Borderless,
+}
+
+impl WindowMode
+{
+	pub const ALL: [WindowMode; 3] = [
+		WindowMode::Windowed,
+		WindowMode::Fullscreen,
+		WindowMode::Borderless,
+	];
+
+	pub fn name(self) -> &'static str
+	{
+		match self
+		{
+			WindowMode::Windowed => "windowed",
+			WindowMode::Fullscreen => "fullscreen",
+			WindowMode::Borderless => "borderless",
+		}
+	}
+
+	fn parse(s: &str) -> Option<Self>
+	{
+		match s
+		{
+			"windowed" => Some(WindowMode::Windowed),
+			"fullscreen" => Some(WindowMode::Fullscreen),
+			"borderless" => Some(WindowMode::Borderless),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowSettings
+{
+	pub mode:            WindowMode,
+	pub size:            (u32, u32),
+	/// Position of the top left corner, the window is centered if not given.
+	pub position:        Option<(i32, i32)>,
+	pub vsync:           bool,
+	/// Whether blocks are only scaled by whole multiples of the skin's tile size, keeping pixel art
+	/// sharp.
+	pub integer_scaling: bool,
+}
+
+impl Default for WindowSettings
+{
+	fn default() -> Self
+	{
+		WindowSettings {
+			mode:            WindowMode::Windowed,
+			size:            (1080, 720),
+			position:        None,
+			vsync:           false,
+			integer_scaling: false,
+		}
+	}
+}
+
+/// Settings of the user, applying to every profile.
+#[derive(Debug, Clone)]
+pub struct Settings
+{
+	/// Whether particles are shown for clears and other game events.
+	pub particles:       bool,
+	/// Factor applied to the number of particles emitted.
+	pub particle_amount: f64,
+
+	pub window: WindowSettings,
+
+	pub ghost: GhostMode,
+
+	pub screenshot_format: ImageFormat,
+	/// Block size in pixels of exported fields.
+	pub export_block_size: u32,
+}
+
+impl Default for Settings
+{
+	fn default() -> Self
+	{
+		Settings {
+			particles:       true,
+			particle_amount: 1.0,
+			window:          WindowSettings::default(),
+
+			ghost: GhostMode::Tile,
+
+			screenshot_format: ImageFormat::Png,
+			export_block_size: 32,
+		}
+	}
+}
+
+/// File holding the settings, inside the user data directory.
+pub fn settings_file() -> PathBuf
+{
+	storage::data_dir().join("settings.ini")
+}
+
+/// Loads the settings, using the default of every missing or invalid value. A settings file with
+/// the defaults is written if there is none yet, for the user to edit.
+pub fn load() -> Settings
+{
+	let file = settings_file();
+	let mut s = Settings::default();
+
+	let ini = match Ini::load_from_file(&file)
+	{
+		Ok(ini) => ini,
+		Err(_) =>
+		{
+			if !file.exists()
+			{
+				if let Err(e) = save(&s)
+				{
+					warn!("Couldn't write the default settings: {}", e.message());
+				}
+			}
+			else
+			{
+				warn!("Couldn't read the settings \"{}\".", file.display());
+			}
+
+			return s;
+		},
+	};
+
+	if let Some(sec) = ini.section(Some(EFFECTS_SECTION))
+	{
+		s.particles = load_property(sec, "particles").unwrap_or(s.particles);
+		s.particle_amount =
+			load_property::<f64>(sec, "particle_amount").unwrap_or(s.particle_amount).max(0.0);
+	}
+
+	if let Some(sec) = ini.section(Some(WINDOW_SECTION))
+	{
+		let w = &mut s.window;
+
+		if let Some(m) = sec.get("mode")
+		{
+			match WindowMode::parse(m)
+			{
+				Some(m) => w.mode = m,
+				None => warn!("Unknown window mode \"{}\".", m),
+			}
+		}
+
+		if let (Some(width), Some(height)) =
+			(load_property(sec, "width"), load_property(sec, "height"))
+		{
+			if width > 0 && height > 0
+			{
+				w.size = (width, height);
+			}
+		}
+
+		if let (Some(x), Some(y)) = (load_property(sec, "x"), load_property(sec, "y"))
+		{
+			w.position = Some((x, y));
+		}
+
+		w.vsync = load_property(sec, "vsync").unwrap_or(w.vsync);
+		w.integer_scaling = load_property(sec, "integer_scaling").unwrap_or(w.integer_scaling);
+	}
+
+	if let Some(g) = ini.section(Some(GAME_SECTION)).and_then(|sec| sec.get("ghost"))
+	{
+		match GhostMode::parse(g)
+		{
+			Some(g) => s.ghost = g,
+			None => warn!("Unknown ghost mode \"{}\".", g),
+		}
+	}
+
+	if let Some(sec) = ini.section(Some(SCREENSHOTS_SECTION))
+	{
+		if let Some(f) = sec.get("format")
+		{
+			match ImageFormat::parse(f)
+			{
+				Some(f) => s.screenshot_format = f,
+				None => warn!("Unknown screenshot format \"{}\".", f),
+			}
+		}
+
+		s.export_block_size =
+			load_property(sec, "field_block_size").unwrap_or(s.export_block_size).max(1);
+	}
+
+	s
+}
+
+/// Writes the settings to the settings file.
+pub fn save(s: &Settings) -> Result<(), Error>
+{
+	let file = settings_file();
+	if let Some(dir) = file.parent()
+	{
+		std::fs::create_dir_all(dir)?;
+	}
+
+	let mut ini = Ini::new();
+	ini.with_section(Some(EFFECTS_SECTION))
+		.set("particles", s.particles.to_string())
+		.set("particle_amount", s.particle_amount.to_string());
+
+	let w = &s.window;
+	ini.with_section(Some(WINDOW_SECTION))
+		.set("mode", w.mode.name())
+		.set("width", w.size.0.to_string())
+		.set("height", w.size.1.to_string())
+		.set("vsync", w.vsync.to_string())
+		.set("integer_scaling", w.integer_scaling.to_string());
+
+	if let Some((x, y)) = w.position
+	{
+		ini.with_section(Some(WINDOW_SECTION)).set("x", x.to_string()).set("y", y.to_string());
+	}
+
+	ini.with_section(Some(GAME_SECTION)).set("ghost", s.ghost.name());
+
+	ini.with_section(Some(SCREENSHOTS_SECTION))
+		.set("format", s.screenshot_format.extension())
+		.set("field_block_size", s.export_block_size.to_string());
+
+	info!("Saving settings \"{}\".", file.display());
+	ini.write_to_file(&file)?;
+
+	Ok(())
+}
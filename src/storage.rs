@@ -0,0 +1,249 @@
+use std::path::{Path, PathBuf};
+
+use ini::Ini;
+use log::{info, warn};
+use rlua::prelude::*;
+
+use crate::error::Error;
+
+/// Environment variable overriding the location of the user data directory.
+pub const DATA_ENV: &str = "DEDTRIS_DATA";
+
+/// Registry table holding the stored values of the running profile.
+const VALUES_KEY: &str = "dedtris.storage";
+
+/// Registry string holding the file the values are flushed to.
+const FILE_KEY: &str = "dedtris.storage_file";
+
+// -----------------------------------------------------------------------------
+// User Data
+// -----------------------------------------------------------------------------
+
+/// Directory for data kept between runs. Checked in order are the `DEDTRIS_DATA` environment
+/// variable and the platform's data directory, falling back to the working directory.
+pub fn data_dir() -> PathBuf
+{
+	if let Some(p) = std::env::var_os(DATA_ENV)
+	{
+		return PathBuf::from(p);
+	}
+
+	let env = |name| std::env::var_os(name).map(PathBuf::from);
+
+	let base = if cfg!(windows)
+	{
+		env("APPDATA")
+	}
+	else if cfg!(target_os = "macos")
+	{
+		env("HOME").map(|h| h.join("Library/Application Support"))
+	}
+	else
+	{
+		env("XDG_DATA_HOME").or_else(|| env("HOME").map(|h| h.join(".local/share")))
+	};
+
+	base.unwrap_or_else(|| PathBuf::from(".")).join("dedtris")
+}
+
+/// File storing the values of the profile with the given identifier.
+pub fn profile_file(id: &str) -> PathBuf
+{
+	data_dir().join("storage").join(format!("{}.ini", id))
+}
+
+// -----------------------------------------------------------------------------
+// Personal Bests
+// -----------------------------------------------------------------------------
+
+/// Best results reached with a profile, each possibly from a different game.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Record
+{
+	pub score: u64,
+	pub lines: u64,
+	pub pps:   f64,
+}
+
+/// Pieces a game needs for its pieces per second to count as a personal best.
+pub const MIN_PPS_PIECES: u64 = 10;
+
+fn record_file(id: &str) -> PathBuf
+{
+	data_dir().join("records").join(format!("{}.ini", id))
+}
+
+/// Returns the personal bests of a profile, `None` if no game was finished yet.
+pub fn load_record(id: &str) -> Option<Record>
+{
+	let ini = Ini::load_from_file(record_file(id)).ok()?;
+	let sec = ini.general_section();
+
+	let get = |key| sec.get(key).and_then(|v| v.parse().ok());
+
+	Some(Record {
+		score: get("score")?,
+		lines: get("lines")?,
+		pps:   sec.get("pps").and_then(|v| v.parse().ok())?,
+	})
+}
+
+/// Merges a game's results into the personal bests of a profile.
+pub fn save_record(id: &str, r: &Record) -> Result<Record, Error>
+{
+	let best = match load_record(id)
+	{
+		Some(b) => Record {
+			score: b.score.max(r.score),
+			lines: b.lines.max(r.lines),
+			pps:   b.pps.max(r.pps),
+		},
+		None => *r,
+	};
+
+	let file = record_file(id);
+	if let Some(dir) = file.parent()
+	{
+		std::fs::create_dir_all(dir)?;
+	}
+
+	let mut ini = Ini::new();
+	ini.with_general_section()
+		.set("score", best.score.to_string())
+		.set("lines", best.lines.to_string())
+		.set("pps", best.pps.to_string());
+
+	info!("Saving records \"{}\".", file.display());
+	ini.write_to_file(&file)?;
+
+	Ok(best)
+}
+
+// -----------------------------------------------------------------------------
+// Value Encoding
+// -----------------------------------------------------------------------------
+
+fn encode(v: &LuaValue) -> Option<String>
+{
+	match v
+	{
+		LuaValue::Boolean(b) => Some(format!("b:{}", b)),
+		LuaValue::Integer(i) => Some(format!("i:{}", i)),
+		LuaValue::Number(n) => Some(format!("n:{}", n)),
+		LuaValue::String(s) => s.to_str().ok().map(|s| format!("s:{}", s)),
+		_ => None,
+	}
+}
+
+fn decode<'a>(ctx: rlua::Context<'a>, s: &str) -> Option<LuaValue<'a>>
+{
+	let (kind, v) = (s.get(..2)?, s.get(2..)?);
+
+	match kind
+	{
+		"b:" => v.parse().ok().map(LuaValue::Boolean),
+		"i:" => v.parse().ok().map(LuaValue::Integer),
+		"n:" => v.parse().ok().map(LuaValue::Number),
+		"s:" => ctx.create_string(v).ok().map(LuaValue::String),
+		_ => None,
+	}
+}
+
+// -----------------------------------------------------------------------------
+// Lua Storage
+// -----------------------------------------------------------------------------
+
+/// Loads the values stored in `file` and exposes them to scripts through the global `storage`
+/// table:
+///
+/// * `storage.get(key [, default])` returns the stored value or `default`.
+/// * `storage.set(key, value)` stores a boolean, number or string, `nil` removes the key.
+pub fn load(ctx: &rlua::Context, file: &Path) -> Result<(), Error>
+{
+	let values = ctx.create_table()?;
+
+	if file.is_file()
+	{
+		info!("Loading storage \"{}\".", file.display());
+
+		let ini = Ini::load_from_file(file)?;
+
+		for (k, v) in ini.general_section().iter()
+		{
+			match decode(*ctx, v)
+			{
+				Some(v) => values.set(k, v)?,
+				None => warn!("Skipping malformed storage value \"{}\".", k),
+			}
+		}
+	}
+
+	ctx.set_named_registry_value(VALUES_KEY, values)?;
+	ctx.set_named_registry_value(FILE_KEY, file.display().to_string())?;
+
+	let get = ctx.create_function(|ctx, (key, default): (String, LuaValue)| {
+		let values: LuaTable = ctx.named_registry_value(VALUES_KEY)?;
+
+		match values.get::<_, LuaValue>(key)?
+		{
+			LuaValue::Nil => Ok(default),
+			v => Ok(v),
+		}
+	})?;
+
+	let set = ctx.create_function(|ctx, (key, value): (String, LuaValue)| {
+		if !matches!(value, LuaValue::Nil) && encode(&value).is_none()
+		{
+			return Err(LuaError::RuntimeError(format!(
+				"Can't store \"{}\", only booleans, numbers and strings can be stored.",
+				key
+			)));
+		}
+
+		let values: LuaTable = ctx.named_registry_value(VALUES_KEY)?;
+		values.set(key, value)
+	})?;
+
+	let storage = ctx.create_table()?;
+	storage.set("get", get)?;
+	storage.set("set", set)?;
+
+	ctx.globals().set("storage", storage)?;
+
+	Ok(())
+}
+
+/// Writes the stored values back to their file.
+pub fn flush(ctx: &rlua::Context) -> Result<(), Error>
+{
+	let values: LuaTable = ctx.named_registry_value(VALUES_KEY)?;
+	let file: String = ctx.named_registry_value(FILE_KEY)?;
+	let file = Path::new(&file);
+
+	let mut ini = Ini::new();
+
+	for pair in values.pairs::<String, LuaValue>()
+	{
+		let (k, v) = pair?;
+
+		if let Some(v) = encode(&v)
+		{
+			ini.with_general_section().set(k, v);
+		}
+	}
+
+	if ini.general_section().is_empty() && !file.exists()
+	{
+		return Ok(());
+	}
+
+	if let Some(dir) = file.parent()
+	{
+		std::fs::create_dir_all(dir)?;
+	}
+
+	info!("Saving storage \"{}\".", file.display());
+	ini.write_to_file(file)?;
+
+	Ok(())
+}